@@ -1,3 +1,18 @@
+use anchor_lang::prelude::*;
+
 pub const MAX_PARTICIPANTS: usize = 20;
 pub const MAX_CYCLES: usize = 20; 
-pub const MIN_CYCLE_DURATION: i64 = 1;
\ No newline at end of file
+pub const MIN_CYCLE_DURATION: i64 = 1;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const REVEAL_WINDOW: i64 = 60 * 60;
+pub const EMERGENCY_QUORUM_BPS: u64 = 5_000;
+pub const DISSOLVE_QUORUM_BPS: u64 = 5_000;
+pub const PAYOUT_VOTE_QUORUM_BPS: u64 = 5_000;
+pub const MAX_FOREMAN_COMMISSION_BPS: u16 = 500;
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+pub const MAX_ALLOWED_MINTS: usize = 16;
+pub const MAINTENANCE_MARGIN_BPS: u64 = 8_000;
+pub const LIQUIDATION_BOUNTY_BPS: u64 = 500;
+pub const MAX_PRICE_AGE: i64 = 60;
+pub const MAX_PRICE_CONFIDENCE_BPS: u64 = 200;
+pub const PYTH_ORACLE_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
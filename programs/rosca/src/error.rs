@@ -40,7 +40,7 @@ pub enum ChitFundError {
     InvalidDisbursementSchedule,
     #[msg("Exceeds the maximum number of participants allowed.")]
     ExceedsMaximumParticipants,
-    #[msg("Total disbursement exceeds total contributions")]
+    #[msg("Total disbursement must equal total contributions")]
     InvalidDisbursementTotal,
     #[msg("Cycle duration is too short, minimum duration required")]
     CycleDurationTooShort,
@@ -52,4 +52,154 @@ pub enum ChitFundError {
     NoEligibleBorrowers,
     #[msg("Cannot withdraw collateral before completing the borrowing cycle")]
     WithdrawBeforeBorrowing,
+    #[msg("Participant does not belong to this chit fund")]
+    ParticipantFundMismatch,
+    #[msg("Maximum discount must not exceed 10000 basis points")]
+    InvalidDiscountCap,
+    #[msg("This chit fund selects borrowers by auction")]
+    AuctionFund,
+    #[msg("This chit fund does not run auctions")]
+    NotAuctionFund,
+    #[msg("Bid discount exceeds the maximum allowed for this cycle")]
+    DiscountTooHigh,
+    #[msg("Bidding for this cycle has closed")]
+    BiddingClosed,
+    #[msg("Auction for this cycle is still open")]
+    AuctionNotClosed,
+    #[msg("Auction has already been settled")]
+    AuctionAlreadySettled,
+    #[msg("No dividend available to claim")]
+    NoDividendAvailable,
+    #[msg("Seed commitments for this cycle are closed")]
+    CommitPhaseClosed,
+    #[msg("Seed already committed for this cycle")]
+    AlreadyCommitted,
+    #[msg("Seed reveals open once the cycle is complete")]
+    RevealPhaseNotOpen,
+    #[msg("Seed reveal window has ended")]
+    RevealPhaseClosed,
+    #[msg("No seed commitment for this cycle")]
+    NoCommitment,
+    #[msg("Seed already revealed for this cycle")]
+    AlreadyRevealed,
+    #[msg("Revealed seed does not match the commitment")]
+    InvalidReveal,
+    #[msg("Waiting for committed seeds to be revealed")]
+    RevealsPending,
+    #[msg("Winner for this cycle has already been drawn")]
+    WinnerAlreadyDrawn,
+    #[msg("Winner for this cycle has not been drawn")]
+    WinnerNotDrawn,
+    #[msg("Recent slot hash unavailable")]
+    SlotHashUnavailable,
+    #[msg("Contribution deadline for this cycle has not passed")]
+    ContributionWindowOpen,
+    #[msg("Contribution deadline must fall within the cycle")]
+    InvalidContributionDeadline,
+    #[msg("Grace period must not be negative")]
+    InvalidGracePeriod,
+    #[msg("Late fee must not exceed 10000 basis points")]
+    InvalidLateFee,
+    #[msg("An emergency request has already been made")]
+    EmergencyAlreadyRequested,
+    #[msg("No pending emergency request")]
+    NoEmergencyRequest,
+    #[msg("Members cannot vote on their own emergency request")]
+    CannotVoteOwnRequest,
+    #[msg("Another emergency request already holds priority")]
+    PriorityBorrowerPending,
+    #[msg("Minimum participants must equal the number of cycles")]
+    InvalidMinParticipants,
+    #[msg("Enrollment deadline must be in the future")]
+    InvalidEnrollmentDeadline,
+    #[msg("Enrollment for this chit fund is closed")]
+    EnrollmentClosed,
+    #[msg("Not enough participants to start the chit fund")]
+    NotEnoughParticipants,
+    #[msg("Chit fund cannot be cancelled")]
+    CannotCancelFund,
+    #[msg("Refunds open once the chit fund is cancelled or dissolved")]
+    RefundsNotOpen,
+    #[msg("Refund has already been claimed")]
+    RefundAlreadyClaimed,
+    #[msg("Dissolution requires the creator, a majority vote or repeated defaults")]
+    CannotDissolveFund,
+    #[msg("Every participant account must be supplied in roster order")]
+    InvalidParticipantAccounts,
+    #[msg("Participant still has a balance or obligation in the chit fund")]
+    ParticipantNotSettled,
+    #[msg("Chit fund still has open participants or vault balances")]
+    ChitFundNotSettled,
+    #[msg("Collateral has already been withdrawn")]
+    CollateralAlreadyWithdrawn,
+    #[msg("Price feed account is not a supported oracle for this chit fund")]
+    InvalidPriceFeed,
+    #[msg("A price feed is required when collateral differs from the contribution mint")]
+    PriceFeedRequired,
+    #[msg("Price feed has not been updated recently enough")]
+    StalePrice,
+    #[msg("Price feed returned an unusable price")]
+    InvalidPrice,
+    #[msg("Collateral amount must be greater than zero")]
+    InvalidCollateralAmount,
+    #[msg("Participant collateral is above the maintenance margin")]
+    CollateralAboveMaintenance,
+    #[msg("Participant has already been liquidated")]
+    AlreadyLiquidated,
+    #[msg("Only members who have already borrowed can be liquidated")]
+    LiquidationBeforeBorrowing,
+    #[msg("Foreman commission exceeds the allowed maximum")]
+    InvalidForemanCommission,
+    #[msg("Commission must be paid to a token account owned by the creator")]
+    InvalidCreatorFeeAccount,
+    #[msg("Protocol fee exceeds the allowed maximum")]
+    InvalidProtocolFee,
+    #[msg("Protocol bounds must lie within the program limits")]
+    InvalidProtocolBounds,
+    #[msg("Mint is not on the protocol allowlist")]
+    MintNotAllowed,
+    #[msg("Mint is already on the protocol allowlist")]
+    MintAlreadyAllowed,
+    #[msg("Protocol mint allowlist is full")]
+    AllowlistFull,
+    #[msg("Protocol fee must be paid to a token account owned by the treasury")]
+    InvalidTreasuryAccount,
+    #[msg("Mint has a freeze authority")]
+    MintHasFreezeAuthority,
+    #[msg("Mint has a permanent delegate")]
+    MintHasPermanentDelegate,
+    #[msg("Mint has a transfer hook")]
+    MintHasTransferHook,
+    #[msg("Mint charges a transfer fee")]
+    MintHasTransferFee,
+    #[msg("Transfer fee could not be calculated for this amount")]
+    TransferFeeOverflow,
+    #[msg("A token account is required unless the mint is native SOL")]
+    TokenAccountRequired,
+    #[msg("Paying out in SOL requires the member's wallet and the unwrap account")]
+    UnwrapAccountRequired,
+    #[msg("This chit fund does not draw borrowers by lottery")]
+    NotLotteryFund,
+    #[msg("This chit fund does not choose borrowers by vote")]
+    NotVoteFund,
+    #[msg("This chit fund does not pay out in a fixed order")]
+    NotFixedOrderFund,
+    #[msg("Payout order must list each member exactly once")]
+    InvalidPayoutOrder,
+    #[msg("A fund needs exactly one member per cycle")]
+    ParticipantsCycleMismatch,
+    #[msg("Candidate is not a member who can still receive a pot")]
+    InvalidVoteCandidate,
+    #[msg("Price feed confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    #[msg("Test oracle is only available in builds with the test-oracle feature")]
+    TestOracleDisabled,
+    #[msg("Voting for this cycle's payout has closed")]
+    VotingClosed,
+    #[msg("Voting for this cycle's payout is still open")]
+    VotingOpen,
+    #[msg("The fund may still need this account")]
+    AccountStillInUse,
+    #[msg("Member has made the most emergency requests allowed")]
+    TooManyEmergencyRequests,
 }
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::extension::{
+    permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig,
+    transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions,
+};
+use anchor_spl::token_2022::spl_token_2022::state::Mint as MintState;
+
+use crate::error::*;
+use crate::state::ProtocolConfig;
+
+// Rejects mint features that let a third party move or freeze pooled funds, or that change
+// how much a transfer delivers, unless the protocol config allows them
+pub fn check_mint_extensions(mint: &AccountInfo, config: &ProtocolConfig) -> Result<()> {
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    if mint_state.base.freeze_authority.is_some() {
+        require!(config.allow_freeze_authority, ChitFundError::MintHasFreezeAuthority);
+    }
+
+    if let Ok(extension) = mint_state.get_extension::<PermanentDelegate>() {
+        if Option::<Pubkey>::from(extension.delegate).is_some() {
+            require!(config.allow_permanent_delegate, ChitFundError::MintHasPermanentDelegate);
+        }
+    }
+
+    if let Ok(extension) = mint_state.get_extension::<TransferHook>() {
+        if Option::<Pubkey>::from(extension.program_id).is_some() {
+            require!(config.allow_transfer_hook, ChitFundError::MintHasTransferHook);
+        }
+    }
+
+    // The fee authority can raise the fee at any time, so any fee config counts
+    if mint_state.get_extension::<TransferFeeConfig>().is_ok() {
+        require!(config.allow_transfer_fee, ChitFundError::MintHasTransferFee);
+    }
+
+    Ok(())
+}
+
+// Fee a transfer-fee mint withholds from a transfer of `amount` this epoch
+pub fn transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    let epoch = Clock::get()?.epoch;
+    fee_config
+        .calculate_epoch_fee(epoch, amount)
+        .ok_or_else(|| error!(ChitFundError::TransferFeeOverflow))
+}
+
+// Amount to send so that `net` arrives after the mint's transfer fee
+pub fn gross_up_for_fee(mint: &AccountInfo, net: u64) -> Result<u64> {
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<MintState>::unpack(&data)?;
+
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(net);
+    };
+    let epoch = Clock::get()?.epoch;
+    fee_config
+        .get_epoch_fee(epoch)
+        .calculate_pre_fee_amount(net)
+        .ok_or_else(|| error!(ChitFundError::TransferFeeOverflow))
+}
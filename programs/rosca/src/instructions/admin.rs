@@ -4,9 +4,12 @@ use anchor_spl::token_interface::{Mint, TokenAccount};
 
 use crate::constants::*;
 use crate::error::*;
-use crate::state::ChitFund;
+use crate::extensions::check_mint_extensions;
+use crate::oracle::read_price_feed;
+use crate::state::{ChitFund, FundStatus, ProtocolConfig, SelectionMode};
 
 #[derive(Accounts)]
+#[instruction(fund_id: u64)]
 pub struct InitializeChitFund<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,  
@@ -14,8 +17,9 @@ pub struct InitializeChitFund<'info> {
         init,
         token::mint = mint,
         token::authority = contribution_vault,
+        token::token_program = token_program,
         payer = creator,
-        seeds = [b"contribution_vault", mint.key().as_ref()],
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
         bump,
         
     )]
@@ -23,10 +27,11 @@ pub struct InitializeChitFund<'info> {
     
     #[account(
         init,
-        token::mint = mint,
+        token::mint = collateral_mint,
         token::authority = collateral_vault,
+        token::token_program = collateral_token_program,
         payer = creator,
-        seeds = [b"collateral_vault", mint.key().as_ref()],
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
         bump
         
     )]
@@ -35,53 +40,172 @@ pub struct InitializeChitFund<'info> {
         init,
         payer = creator,
         space = 8 + ChitFund::INIT_SPACE,
-        seeds = [mint.key().as_ref()],
+        seeds = [b"chit_fund", creator.key().as_ref(), &fund_id.to_le_bytes()],
         bump,
     )]
     pub chit_fund: Box<Account<'info, ChitFund>>,
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+    #[account(mint::token_program = token_program)]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mint::token_program = collateral_token_program)]
+    pub collateral_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Read through the oracle adapters; required only when the mints differ
+    pub price_feed: Option<UncheckedAccount<'info>>,
     pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn initialize_chit_fund(
     ctx: Context<InitializeChitFund>,
+    fund_id: u64,
     contribution_amount: u64,
     cycle_duration: i64,
     total_cycles: u8,
     collateral_requirement: u64,
     max_participants: u8,
     disbursement_schedule: [u64; MAX_CYCLES],
+    max_discount_bps: u16,
+    contribution_deadline_offset: i64,
+    grace_period: i64,
+    late_fee_bps: u16,
+    min_participants: u8,
+    enrollment_deadline: i64,
+    payout_holdback: bool,
+    foreman_commission_bps: u16,
+    selection_mode: SelectionMode,
+    payout_order: Vec<u8>,
 ) -> Result<()> {
+    let config = &ctx.accounts.config;
+
     // Validations
     require!(
-        total_cycles as usize <= MAX_CYCLES,
+        config.is_mint_allowed(&ctx.accounts.mint.key())
+            && config.is_mint_allowed(&ctx.accounts.collateral_mint.key()),
+        ChitFundError::MintNotAllowed
+    );
+    check_mint_extensions(&ctx.accounts.mint.to_account_info(), config)?;
+    check_mint_extensions(&ctx.accounts.collateral_mint.to_account_info(), config)?;
+    require!(
+        total_cycles <= config.max_cycles,
         ChitFundError::ExceedsMaximumCycles
     );
     require!(
-        cycle_duration >= MIN_CYCLE_DURATION,
+        cycle_duration >= config.min_cycle_duration,
         ChitFundError::InvalidCycleDuration
     );
     require!(
-        max_participants as usize <= MAX_PARTICIPANTS,
+        max_participants <= config.max_participants,
         ChitFundError::ExceedsMaximumParticipants
     );
+    require!(
+        max_discount_bps as u64 <= BPS_DENOMINATOR,
+        ChitFundError::InvalidDiscountCap
+    );
+    require!(
+        contribution_deadline_offset > 0 && contribution_deadline_offset <= cycle_duration,
+        ChitFundError::InvalidContributionDeadline
+    );
+    require!(
+        grace_period >= 0,
+        ChitFundError::InvalidGracePeriod
+    );
+    require!(
+        late_fee_bps as u64 <= BPS_DENOMINATOR,
+        ChitFundError::InvalidLateFee
+    );
+    require!(
+        foreman_commission_bps <= config.max_foreman_commission_bps,
+        ChitFundError::InvalidForemanCommission
+    );
+    require!(
+        enrollment_deadline > Clock::get()?.unix_timestamp,
+        ChitFundError::InvalidEnrollmentDeadline
+    );
+    require!(
+        selection_mode != SelectionMode::Auction || max_discount_bps > 0,
+        ChitFundError::InvalidDiscountCap
+    );
+
+    // Every member takes exactly one pot, so the fund needs room for one member per cycle
+    require!(
+        total_cycles <= max_participants,
+        ChitFundError::ParticipantsCycleMismatch
+    );
+    // start_fund needs that full roster, so a lower minimum would leave a fund that can
+    // neither start nor fail enrollment
+    require!(
+        min_participants > 0 && min_participants == total_cycles,
+        ChitFundError::InvalidMinParticipants
+    );
+
+    ChitFund::validate_disbursement_schedule(&disbursement_schedule, total_cycles, contribution_amount)?;
+
+    // A fixed order can be agreed now or left to start_fund
+    if selection_mode != SelectionMode::FixedOrder {
+        require!(payout_order.is_empty(), ChitFundError::NotFixedOrderFund);
+    }
+    let has_payout_order = !payout_order.is_empty();
+    let parsed_payout_order = if has_payout_order {
+        ChitFund::parse_payout_order(&payout_order, total_cycles)?
+    } else {
+        [0; MAX_CYCLES]
+    };
+
+    // Collateral in another mint is valued through a price feed
+    let price_feed = if ctx.accounts.collateral_mint.key() == ctx.accounts.mint.key() {
+        Pubkey::default()
+    } else {
+        let price_feed = ctx.accounts.price_feed.as_ref().ok_or(ChitFundError::PriceFeedRequired)?;
+        read_price_feed(price_feed)?;
+        price_feed.key()
+    };
 
     let chit_fund = &mut ctx.accounts.chit_fund;
 
     // Admin/Config data
     chit_fund.creator = ctx.accounts.creator.key();
+    chit_fund.fund_id = fund_id;
+    chit_fund.bump = ctx.bumps.chit_fund;
     chit_fund.mint_address = ctx.accounts.mint.key();
+    chit_fund.collateral_mint = ctx.accounts.collateral_mint.key();
+    chit_fund.price_feed = price_feed;
     chit_fund.contribution_amount = contribution_amount;
     chit_fund.cycle_duration = cycle_duration;
     chit_fund.total_cycles = total_cycles;
     chit_fund.collateral_requirement = collateral_requirement;
     chit_fund.max_participants = max_participants;
+    chit_fund.min_participants = min_participants;
+    chit_fund.enrollment_deadline = enrollment_deadline;
+    chit_fund.selection_mode = selection_mode;
+    chit_fund.max_discount_bps = max_discount_bps;
+    chit_fund.contribution_deadline_offset = contribution_deadline_offset;
+    chit_fund.grace_period = grace_period;
+    chit_fund.late_fee_bps = late_fee_bps;
+    chit_fund.payout_holdback = payout_holdback;
+    chit_fund.foreman_commission_bps = foreman_commission_bps;
+    chit_fund.protocol_fee_bps = ctx.accounts.config.protocol_fee_bps;
+    chit_fund.protocol_treasury = ctx.accounts.config.treasury;
 
     // State tracking
     chit_fund.current_cycle = 0;
-    chit_fund.is_active = true;
-    chit_fund.last_disbursement_time = Clock::get()?.unix_timestamp;
+    chit_fund.status = FundStatus::Enrolling;
+    chit_fund.last_disbursement_time = 0;  // Cycle 0 starts in start_fund
+    chit_fund.draw_seed = [0; 32];
+    chit_fund.commit_count = 0;
+    chit_fund.reveal_count = 0;
+    chit_fund.unrevealed = [false; MAX_PARTICIPANTS];
+    chit_fund.selected_borrower = Pubkey::default();
+    chit_fund.priority_borrower = Pubkey::default();
+    chit_fund.emergency_queue = [false; MAX_PARTICIPANTS];
+    chit_fund.payout_order = parsed_payout_order;
+    chit_fund.has_payout_order = has_payout_order;
+    chit_fund.payout_votes = [0; MAX_PARTICIPANTS];
 
     // Participant tracking
     chit_fund.participants = [Pubkey::default(); MAX_PARTICIPANTS];
@@ -92,15 +216,34 @@ pub fn initialize_chit_fund(
     chit_fund.contribution_vault = ctx.accounts.contribution_vault.key();
     chit_fund.collateral_vault = ctx.accounts.collateral_vault.key();
     chit_fund.total_contribution_amount = 0;
+    chit_fund.total_collateral_amount = 0;
+    chit_fund.seized_collateral = 0;
+    chit_fund.pot_shortfall = 0;
+    chit_fund.dividend_per_member = 0;
+    chit_fund.late_fees_collected = 0;
+    chit_fund.foreman_commissions_paid = 0;
+    chit_fund.protocol_fees_paid = 0;
+    chit_fund.default_count = 0;
+    chit_fund.dissolve_votes = 0;
+    chit_fund.participants_closed = 0;
     chit_fund.borrowed_participants = [false; MAX_PARTICIPANTS];
+    chit_fund.paid_participants = [false; MAX_PARTICIPANTS];
+    chit_fund.paid_count = 0;
 
     emit!(ChitFundInitialized {
         chit_fund: chit_fund.key(),
         creator: chit_fund.creator,
+        fund_id,
         contribution_amount,
         total_cycles,
         max_participants,
+        selection_mode,
+        max_discount_bps,
+        foreman_commission_bps,
+        protocol_fee_bps: chit_fund.protocol_fee_bps,
         mint_address: chit_fund.mint_address,
+        collateral_mint: chit_fund.collateral_mint,
+        price_feed: chit_fund.price_feed,
     });
 
     Ok(())
@@ -110,8 +253,76 @@ pub fn initialize_chit_fund(
 pub struct ChitFundInitialized {
     pub chit_fund: Pubkey,
     pub creator: Pubkey,
+    pub fund_id: u64,
     pub contribution_amount: u64,
     pub total_cycles: u8,
     pub max_participants: u8,
+    pub selection_mode: SelectionMode,
+    pub max_discount_bps: u16,
+    pub foreman_commission_bps: u16,
+    pub protocol_fee_bps: u16,
     pub mint_address: Pubkey,
-}
\ No newline at end of file
+    pub collateral_mint: Pubkey,
+    pub price_feed: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct StartFund<'info> {
+    pub creator: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        has_one = creator @ ChitFundError::Unauthorized,
+        constraint = chit_fund.status == FundStatus::Enrolling @ ChitFundError::EnrollmentClosed,
+        constraint = chit_fund.participants_count >= chit_fund.min_participants @ ChitFundError::NotEnoughParticipants,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+}
+
+// A fixed-order fund takes its payout order here if none was set at init, falling back to join order
+pub fn start_chit_fund(ctx: Context<StartFund>, payout_order: Vec<u8>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // With fewer members some cycles would have no one to pay, and with more some members
+    // would never be paid while still owing collateral-backed contributions
+    require!(
+        chit_fund.participants_count == chit_fund.total_cycles,
+        ChitFundError::ParticipantsCycleMismatch
+    );
+
+    if chit_fund.selection_mode == SelectionMode::FixedOrder {
+        if !payout_order.is_empty() {
+            chit_fund.payout_order = ChitFund::parse_payout_order(&payout_order, chit_fund.total_cycles)?;
+        } else if !chit_fund.has_payout_order {
+            for cycle in 0..chit_fund.total_cycles {
+                chit_fund.payout_order[cycle as usize] = cycle;
+            }
+        }
+        chit_fund.has_payout_order = true;
+    } else {
+        require!(payout_order.is_empty(), ChitFundError::NotFixedOrderFund);
+    }
+
+    // Freeze the roster and start the first cycle's clock
+    chit_fund.status = FundStatus::Active;
+    chit_fund.current_cycle = 0;
+    chit_fund.last_disbursement_time = current_time;
+
+    emit!(ChitFundStarted {
+        chit_fund: chit_fund.key(),
+        participants_count: chit_fund.participants_count,
+        start_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ChitFundStarted {
+    pub chit_fund: Pubkey,
+    pub participants_count: u8,
+    pub start_time: i64,
+}
@@ -0,0 +1,359 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{ChitFund, CycleAuction, Participant, SelectionMode};
+use crate::error::*;
+use crate::payout::{pay_out_pot, PotAccounts};
+use crate::vault::VaultSigner;
+
+#[derive(Accounts)]
+pub struct PlaceBid<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode == SelectionMode::Auction @ ChitFundError::NotAuctionFund,
+        constraint = Clock::get()?.unix_timestamp < chit_fund.cycle_end_time() @ ChitFundError::BiddingClosed,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + CycleAuction::INIT_SPACE,
+        seeds = [b"auction", chit_fund.key().as_ref(), &[chit_fund.current_cycle]],
+        bump,
+        constraint = !auction.is_closed @ ChitFundError::BiddingClosed,
+    )]
+    pub auction: Box<Account<'info, CycleAuction>>,
+
+    #[account(
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = !participant.has_borrowed @ ChitFundError::AlreadyBorrowed,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn place_auction_bid(ctx: Context<PlaceBid>, discount: u64) -> Result<()> {
+    let chit_fund = &ctx.accounts.chit_fund;
+    let auction = &mut ctx.accounts.auction;
+
+    require!(
+        discount <= chit_fund.max_discount(),
+        ChitFundError::DiscountTooHigh
+    );
+
+    // First bid of the cycle creates the auction
+    if auction.chit_fund == Pubkey::default() {
+        auction.chit_fund = chit_fund.key();
+        auction.cycle = chit_fund.current_cycle;
+        auction.payer = ctx.accounts.user.key();
+    }
+
+    // Highest discount means lowest payout, ties go to the earlier bid
+    if auction.bid_count == 0 || discount > auction.best_discount {
+        auction.best_bidder = ctx.accounts.user.key();
+        auction.best_discount = discount;
+    }
+    auction.bid_count = auction.bid_count.saturating_add(1);
+
+    emit!(BidPlaced {
+        chit_fund: chit_fund.key(),
+        bidder: ctx.accounts.user.key(),
+        cycle: auction.cycle,
+        discount,
+        bid_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseAuction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode == SelectionMode::Auction @ ChitFundError::NotAuctionFund,
+        constraint = Clock::get()?.unix_timestamp >= chit_fund.cycle_end_time() @ ChitFundError::CycleNotComplete,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + CycleAuction::INIT_SPACE,
+        seeds = [b"auction", chit_fund.key().as_ref(), &[chit_fund.current_cycle]],
+        bump,
+        constraint = !auction.is_closed @ ChitFundError::BiddingClosed,
+    )]
+    pub auction: Box<Account<'info, CycleAuction>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn close_cycle_auction(ctx: Context<CloseAuction>) -> Result<()> {
+    let chit_fund = &ctx.accounts.chit_fund;
+    let auction = &mut ctx.accounts.auction;
+
+    // Nobody bid, so the auction is created here
+    if auction.chit_fund == Pubkey::default() {
+        auction.chit_fund = chit_fund.key();
+        auction.cycle = chit_fund.current_cycle;
+        auction.payer = ctx.accounts.user.key();
+    }
+
+    if chit_fund.has_priority_borrower() {
+        // An approved emergency request takes the pot undiscounted
+        auction.winner = chit_fund.priority_borrower;
+        auction.best_discount = 0;
+    } else if auction.bid_count > 0 {
+        auction.winner = auction.best_bidder;
+    } else {
+        // Without bids the pot goes undiscounted to the earliest member yet to borrow
+        let mut fallback_winner = None;
+        for i in 0..chit_fund.participants_count as usize {
+            if chit_fund.participants[i] != Pubkey::default() && !chit_fund.borrowed_participants[i] {
+                fallback_winner = Some(chit_fund.participants[i]);
+                break;
+            }
+        }
+        auction.winner = fallback_winner.ok_or(ChitFundError::NoEligibleBorrowers)?;
+        auction.best_discount = 0;
+    }
+
+    let pot = chit_fund.disbursement_schedule[chit_fund.current_cycle as usize];
+    auction.payout_amount = pot - auction.best_discount;
+    auction.is_closed = true;
+
+    emit!(AuctionClosed {
+        chit_fund: chit_fund.key(),
+        cycle: auction.cycle,
+        winner: auction.winner,
+        discount: auction.best_discount,
+        payout_amount: auction.payout_amount,
+        bid_count: auction.bid_count,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.all_contributions_in() @ ChitFundError::PendingContributions,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction", chit_fund.key().as_ref(), &[chit_fund.current_cycle]],
+        bump,
+        constraint = auction.is_closed @ ChitFundError::AuctionNotClosed,
+        constraint = !auction.is_settled @ ChitFundError::AuctionAlreadySettled,
+    )]
+    pub auction: Box<Account<'info, CycleAuction>>,
+
+    #[account(
+        mut,
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = contribution_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub contribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), auction.winner.as_ref()],
+        bump,
+        constraint = winner_participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = !winner_participant.has_borrowed @ ChitFundError::AlreadyBorrowed,
+    )]
+    pub winner_participant: Box<Account<'info, Participant>>,
+
+    // Left out when the winner is paid in SOL
+    #[account(
+        mut,
+        constraint = winner_token_account.key() == winner_participant.usdc_address @ ChitFundError::InvalidBorrowerAccount,
+    )]
+    pub winner_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = winner_collateral_account.key() == winner_participant.collateral_address @ ChitFundError::InvalidBorrowerAccount,
+    )]
+    pub winner_collateral_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Needed when the winner joined without a token account and is paid in SOL
+    #[account(mut, address = winner_participant.owner @ ChitFundError::InvalidBorrowerAccount)]
+    pub winner_wallet: Option<SystemAccount<'info>>,
+
+    /// CHECK: Created, filled and closed within this instruction to unwrap SOL
+    #[account(mut, seeds = [b"unwrap", chit_fund.key().as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    // Left out when the fund charges no commission
+    #[account(
+        mut,
+        constraint = creator_fee_account.owner == chit_fund.creator @ ChitFundError::InvalidCreatorFeeAccount,
+        constraint = creator_fee_account.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub creator_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Left out when the fund charges no protocol fee
+    #[account(
+        mut,
+        constraint = treasury_fee_account.owner == chit_fund.protocol_treasury @ ChitFundError::InvalidTreasuryAccount,
+        constraint = treasury_fee_account.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub treasury_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn settle_cycle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let auction = &mut ctx.accounts.auction;
+    let participant = &mut ctx.accounts.winner_participant;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let payout_amount = auction.payout_amount;
+
+    let chit_fund_key = chit_fund.key();
+    let pot_accounts = PotAccounts {
+        payer: ctx.accounts.user.to_account_info(),
+        contribution_vault: VaultSigner::contribution(
+            &ctx.accounts.contribution_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            chit_fund_key,
+            ctx.bumps.contribution_vault,
+        ),
+        collateral_vault: VaultSigner::collateral(
+            &ctx.accounts.collateral_vault,
+            &ctx.accounts.collateral_mint,
+            &ctx.accounts.collateral_token_program,
+            chit_fund_key,
+            ctx.bumps.collateral_vault,
+        ),
+        recipient_token_account: ctx.accounts.winner_token_account.as_ref().map(|account| account.to_account_info()),
+        recipient_collateral_account: ctx.accounts.winner_collateral_account.as_ref().map(|account| account.to_account_info()),
+        recipient_wallet: ctx.accounts.winner_wallet.as_ref().map(|account| account.to_account_info()),
+        unwrap_account: ctx.accounts.unwrap_account.as_ref().map(|account| account.to_account_info()),
+        unwrap_bump: ctx.bumps.unwrap_account.unwrap_or_default(),
+        creator_fee_account: ctx.accounts.creator_fee_account.as_ref().map(|account| account.to_account_info()),
+        treasury_fee_account: ctx.accounts.treasury_fee_account.as_ref().map(|account| account.to_account_info()),
+        system_program: ctx.accounts.system_program.to_account_info(),
+    };
+    let payout = pay_out_pot(pot_accounts, chit_fund, participant, payout_amount)?;
+
+    auction.is_settled = true;
+
+    // The discount stays in the vault and is shared among the other members
+    let discount = auction.best_discount;
+    let per_member = chit_fund.credit_dividend(discount);
+    if per_member > 0 {
+        participant.dividend_excluded += per_member;
+
+        emit!(DividendDistributed {
+            chit_fund: chit_fund.key(),
+            cycle: auction.cycle,
+            total_discount: discount,
+            per_member,
+            recipients: chit_fund.participants_count - 1,
+        });
+    }
+
+    chit_fund.advance_cycle(current_time);
+
+    emit!(AuctionSettled {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        cycle: auction.cycle,
+        amount: payout_amount,
+        collateral_amount: payout.collateral_amount,
+        held_back: payout.held_back,
+        transfer_fee: payout.transfer_fee,
+        collateral_transfer_fee: payout.collateral_transfer_fee,
+        discount: auction.best_discount,
+        settle_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct DividendDistributed {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub total_discount: u64,
+    pub per_member: u64,
+    pub recipients: u8,
+}
+
+#[event]
+pub struct BidPlaced {
+    pub chit_fund: Pubkey,
+    pub bidder: Pubkey,
+    pub cycle: u8,
+    pub discount: u64,
+    pub bid_time: i64,
+}
+
+#[event]
+pub struct AuctionClosed {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub winner: Pubkey,
+    pub discount: u64,
+    pub payout_amount: u64,
+    pub bid_count: u32,
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub cycle: u8,
+    pub amount: u64,
+    pub collateral_amount: u64,
+    pub held_back: u64,
+    pub transfer_fee: u64,
+    pub collateral_transfer_fee: u64,
+    pub discount: u64,
+    pub settle_time: i64,
+}
@@ -0,0 +1,241 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{ChitFund, FundStatus, Participant};
+use crate::error::*;
+use crate::native::{is_native_mint, Unwrap};
+use crate::vault::VaultSigner;
+
+#[derive(Accounts)]
+pub struct CancelFund<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.status == FundStatus::Enrolling @ ChitFundError::CannotCancelFund,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+}
+
+pub fn cancel_chit_fund(ctx: Context<CancelFund>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // The creator may cancel during enrollment, anyone once enrollment has failed
+    let is_creator = ctx.accounts.user.key() == chit_fund.creator;
+    let enrollment_failed = current_time >= chit_fund.enrollment_deadline
+        && chit_fund.participants_count < chit_fund.min_participants;
+    require!(
+        is_creator || enrollment_failed,
+        ChitFundError::CannotCancelFund
+    );
+
+    chit_fund.status = FundStatus::Cancelled;
+
+    emit!(ChitFundCancelled {
+        chit_fund: chit_fund.key(),
+        cancelled_by: ctx.accounts.user.key(),
+        participants_count: chit_fund.participants_count,
+        cancel_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = matches!(chit_fund.status, FundStatus::Cancelled | FundStatus::Dissolved) @ ChitFundError::RefundsNotOpen,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = contribution_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub contribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = !participant.is_refunded @ ChitFundError::RefundAlreadyClaimed,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    // Either account is left out to take that side of the refund as SOL
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = user,
+        associated_token::token_program = collateral_token_program,
+    )]
+    pub user_collateral_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Created, filled and closed within this instruction to unwrap SOL
+    #[account(mut, seeds = [b"unwrap", chit_fund.key().as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn claim_participant_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    // A dissolved fund pays out the settlement worked out in dissolve_fund
+    let (contribution_refund, settlement_collateral) = match chit_fund.status {
+        FundStatus::Dissolved => (participant.settlement_amount, participant.settlement_collateral),
+        _ => (participant.paid_contributions(chit_fund), 0),
+    };
+    let collateral_refund = participant.remaining_collateral() + settlement_collateral;
+
+    let chit_fund_key = chit_fund.key();
+
+    // Whatever has no token account to go to is unwrapped to the wallet as SOL
+    let unwrap_tokens = contribution_refund > 0 && ctx.accounts.user_token_account.is_none();
+    let unwrap_collateral = collateral_refund > 0 && ctx.accounts.user_collateral_account.is_none();
+    if unwrap_tokens {
+        require!(is_native_mint(&ctx.accounts.mint.key()), ChitFundError::TokenAccountRequired);
+    }
+    if unwrap_collateral {
+        require!(is_native_mint(&ctx.accounts.collateral_mint.key()), ChitFundError::TokenAccountRequired);
+    }
+    let unwrap = if unwrap_tokens || unwrap_collateral {
+        let unwrap_account = ctx.accounts.unwrap_account.as_ref().ok_or(ChitFundError::UnwrapAccountRequired)?;
+        let (native_mint, token_program) = if unwrap_tokens {
+            (ctx.accounts.mint.to_account_info(), ctx.accounts.token_program.to_account_info())
+        } else {
+            (ctx.accounts.collateral_mint.to_account_info(), ctx.accounts.collateral_token_program.to_account_info())
+        };
+        Some(Unwrap::open(
+            unwrap_account.to_account_info(),
+            ctx.accounts.user.to_account_info(),
+            native_mint,
+            token_program,
+            ctx.accounts.system_program.to_account_info(),
+            chit_fund_key,
+            ctx.bumps.unwrap_account.unwrap_or_default(),
+        )?)
+    } else {
+        None
+    };
+    let unwrap_destination = unwrap.as_ref().map(|unwrap| unwrap.account.clone());
+    let token_destination = if unwrap_tokens {
+        unwrap_destination.clone()
+    } else {
+        ctx.accounts.user_token_account.as_ref().map(|account| account.to_account_info())
+    };
+    let collateral_destination = if unwrap_collateral {
+        unwrap_destination
+    } else {
+        ctx.accounts.user_collateral_account.as_ref().map(|account| account.to_account_info())
+    };
+
+    // Return collateral
+    let collateral_transfer_fee = if collateral_refund > 0 {
+        VaultSigner::collateral(
+            &ctx.accounts.collateral_vault,
+            &ctx.accounts.collateral_mint,
+            &ctx.accounts.collateral_token_program,
+            chit_fund_key,
+            ctx.bumps.collateral_vault,
+        ).transfer(collateral_destination.ok_or(ChitFundError::TokenAccountRequired)?, collateral_refund)?
+    } else {
+        0
+    };
+
+    // Return contributions made so far
+    let transfer_fee = if contribution_refund > 0 {
+        VaultSigner::contribution(
+            &ctx.accounts.contribution_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            chit_fund_key,
+            ctx.bumps.contribution_vault,
+        ).transfer(token_destination.ok_or(ChitFundError::TokenAccountRequired)?, contribution_refund)?
+    } else {
+        0
+    };
+
+    if let Some(unwrap) = unwrap {
+        let unwrapped = if unwrap_tokens { contribution_refund } else { 0 }
+            + if unwrap_collateral { collateral_refund } else { 0 };
+        unwrap.close(ctx.accounts.user.to_account_info(), unwrapped)?;
+    }
+
+    // Rounding in the settlement never takes the last claimant's refund below zero
+    chit_fund.total_collateral_amount = chit_fund.total_collateral_amount.saturating_sub(collateral_refund);
+    chit_fund.total_contribution_amount = chit_fund.total_contribution_amount.saturating_sub(contribution_refund);
+    participant.collateral_returned += collateral_refund - settlement_collateral;
+    participant.is_refunded = true;
+
+    emit!(RefundClaimed {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        collateral_amount: collateral_refund,
+        contribution_amount: contribution_refund,
+        transfer_fee,
+        collateral_transfer_fee,
+        refund_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ChitFundCancelled {
+    pub chit_fund: Pubkey,
+    pub cancelled_by: Pubkey,
+    pub participants_count: u8,
+    pub cancel_time: i64,
+}
+
+#[event]
+pub struct RefundClaimed {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub collateral_amount: u64,
+    pub contribution_amount: u64,
+    pub transfer_fee: u64,
+    pub collateral_transfer_fee: u64,
+    pub refund_time: i64,
+}
@@ -0,0 +1,267 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{ChitFund, CycleAuction, DissolveVote, EmergencyVote, Participant, PayoutVote};
+use crate::error::*;
+use crate::native::is_native_mint;
+use crate::vault::VaultSigner;
+
+#[derive(Accounts)]
+pub struct CloseParticipant<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    // Rent goes back to the member who paid for it in join_chit
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = participant.is_settled(&chit_fund) @ ChitFundError::ParticipantNotSettled,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+}
+
+pub fn close_participant_account(ctx: Context<CloseParticipant>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+
+    chit_fund.participants_closed += 1;
+
+    emit!(ParticipantClosed {
+        chit_fund: chit_fund.key(),
+        participant: ctx.accounts.participant.key(),
+        owner: ctx.accounts.user.key(),
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseFund<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    // Rent for the fund and both vaults goes back to the creator who paid it
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        has_one = creator @ ChitFundError::Unauthorized,
+        constraint = chit_fund.has_ended() @ ChitFundError::ChitFundActive,
+        constraint = chit_fund.participants_closed == chit_fund.participants_count @ ChitFundError::ChitFundNotSettled,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    // Vaults holding more than rounding dust still owe someone, so the fund stays open
+    #[account(
+        mut,
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = contribution_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = contribution_vault.amount <= chit_fund.dust_allowance() @ ChitFundError::ChitFundNotSettled,
+    )]
+    pub contribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = collateral_vault.amount <= chit_fund.dust_allowance() @ ChitFundError::ChitFundNotSettled,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn close_chit_fund(ctx: Context<CloseFund>) -> Result<()> {
+    let chit_fund_key = ctx.accounts.chit_fund.key();
+    let dust_tokens = ctx.accounts.contribution_vault.amount;
+    let dust_collateral = ctx.accounts.collateral_vault.amount;
+
+    let contribution_vault = VaultSigner::contribution(
+        &ctx.accounts.contribution_vault,
+        &ctx.accounts.mint,
+        &ctx.accounts.token_program,
+        chit_fund_key,
+        ctx.bumps.contribution_vault,
+    );
+    burn_dust(&contribution_vault, dust_tokens)?;
+    contribution_vault.close(ctx.accounts.creator.to_account_info())?;
+
+    let collateral_vault = VaultSigner::collateral(
+        &ctx.accounts.collateral_vault,
+        &ctx.accounts.collateral_mint,
+        &ctx.accounts.collateral_token_program,
+        chit_fund_key,
+        ctx.bumps.collateral_vault,
+    );
+    burn_dust(&collateral_vault, dust_collateral)?;
+    collateral_vault.close(ctx.accounts.creator.to_account_info())?;
+
+    emit!(ChitFundClosed {
+        chit_fund: chit_fund_key,
+        creator: ctx.accounts.creator.key(),
+        dust_tokens,
+        dust_collateral,
+        close_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+// Native SOL cannot be burned, but closing a wrapped SOL account releases its balance with
+// the rent, so there the dust goes back with it
+fn burn_dust(vault: &VaultSigner, amount: u64) -> Result<()> {
+    if amount > 0 && !is_native_mint(&vault.mint.key()) {
+        vault.burn(amount)?;
+    }
+    Ok(())
+}
+
+// A fund's auctions and votes are done with once it has ended, or has been closed already
+fn fund_has_ended(chit_fund: &AccountInfo) -> Result<bool> {
+    Ok(match load_fund(chit_fund)? {
+        Some(chit_fund) => chit_fund.has_ended(),
+        None => true,
+    })
+}
+
+fn load_fund(chit_fund: &AccountInfo) -> Result<Option<ChitFund>> {
+    if chit_fund.data_is_empty() {
+        return Ok(None);
+    }
+    let data = chit_fund.try_borrow_data()?;
+    Ok(Some(ChitFund::try_deserialize(&mut &data[..])?))
+}
+
+#[derive(Accounts)]
+pub struct CloseAuctionAccount<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: The auction's fund, which may have been closed already
+    #[account(address = auction.chit_fund)]
+    pub chit_fund: UncheckedAccount<'info>,
+
+    // Rent goes back to whoever opened the auction with a bid or by closing it
+    #[account(
+        mut,
+        close = user,
+        constraint = auction.payer == user.key() @ ChitFundError::Unauthorized,
+    )]
+    pub auction: Box<Account<'info, CycleAuction>>,
+}
+
+// A settled auction is not read again, as the next cycle's auction has its own account
+pub fn close_cycle_auction_account(ctx: Context<CloseAuctionAccount>) -> Result<()> {
+    require!(
+        ctx.accounts.auction.is_settled || fund_has_ended(&ctx.accounts.chit_fund)?,
+        ChitFundError::AccountStillInUse
+    );
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClosePayoutVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: The vote's fund, which may have been closed already
+    #[account(address = vote.chit_fund)]
+    pub chit_fund: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        constraint = vote.voter == user.key() @ ChitFundError::Unauthorized,
+    )]
+    pub vote: Box<Account<'info, PayoutVote>>,
+}
+
+// The vote keeps the member from voting twice, so it stays until its cycle has passed
+pub fn close_payout_vote_account(ctx: Context<ClosePayoutVote>) -> Result<()> {
+    let done = match load_fund(&ctx.accounts.chit_fund)? {
+        Some(chit_fund) => chit_fund.has_ended() || chit_fund.current_cycle > ctx.accounts.vote.cycle,
+        None => true,
+    };
+    require!(done, ChitFundError::AccountStillInUse);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseDissolveVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: The vote's fund, which may have been closed already
+    #[account(address = vote.chit_fund)]
+    pub chit_fund: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        constraint = vote.voter == user.key() @ ChitFundError::Unauthorized,
+    )]
+    pub vote: Box<Account<'info, DissolveVote>>,
+}
+
+pub fn close_dissolve_vote_account(ctx: Context<CloseDissolveVote>) -> Result<()> {
+    require!(fund_has_ended(&ctx.accounts.chit_fund)?, ChitFundError::AccountStillInUse);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CloseEmergencyVote<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: The vote's fund, which may have been closed already
+    #[account(address = vote.chit_fund)]
+    pub chit_fund: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        close = user,
+        constraint = vote.voter == user.key() @ ChitFundError::Unauthorized,
+    )]
+    pub vote: Box<Account<'info, EmergencyVote>>,
+}
+
+pub fn close_emergency_vote_account(ctx: Context<CloseEmergencyVote>) -> Result<()> {
+    require!(fund_has_ended(&ctx.accounts.chit_fund)?, ChitFundError::AccountStillInUse);
+    Ok(())
+}
+
+#[event]
+pub struct ParticipantClosed {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct ChitFundClosed {
+    pub chit_fund: Pubkey,
+    pub creator: Pubkey,
+    pub dust_tokens: u64,
+    pub dust_collateral: u64,
+    pub close_time: i64,
+}
@@ -0,0 +1,255 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::program::Rosca;
+use crate::state::ProtocolConfig;
+use crate::constants::*;
+use crate::error::*;
+
+// Only the program's upgrade authority can create the config
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + ProtocolConfig::INIT_SPACE,
+        seeds = [b"protocol_config"],
+        bump,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ChitFundError::Unauthorized)]
+    pub program: Program<'info, Rosca>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ ChitFundError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_protocol_config(
+    ctx: Context<InitializeProtocolConfig>,
+    treasury: Pubkey,
+    protocol_fee_bps: u16,
+    max_participants: u8,
+    max_cycles: u8,
+    min_cycle_duration: i64,
+    max_foreman_commission_bps: u16,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = ctx.accounts.admin.key();
+    config.bump = ctx.bumps.config;
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.treasury = treasury;
+    config.allowed_mints = Vec::new();
+    config.allow_freeze_authority = false;
+    config.allow_permanent_delegate = false;
+    config.allow_transfer_hook = false;
+    config.allow_transfer_fee = false;
+    config.max_participants = max_participants;
+    config.max_cycles = max_cycles;
+    config.min_cycle_duration = min_cycle_duration;
+    config.max_foreman_commission_bps = max_foreman_commission_bps;
+    config.validate()?;
+
+    emit!(ProtocolConfigUpdated {
+        admin: config.admin,
+        treasury: config.treasury,
+        protocol_fee_bps,
+        max_participants,
+        max_cycles,
+        min_cycle_duration,
+        max_foreman_commission_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = config.bump,
+        has_one = admin @ ChitFundError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+}
+
+pub fn update_protocol_config(
+    ctx: Context<UpdateProtocolConfig>,
+    protocol_fee_bps: u16,
+    max_participants: u8,
+    max_cycles: u8,
+    min_cycle_duration: i64,
+    max_foreman_commission_bps: u16,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.protocol_fee_bps = protocol_fee_bps;
+    config.max_participants = max_participants;
+    config.max_cycles = max_cycles;
+    config.min_cycle_duration = min_cycle_duration;
+    config.max_foreman_commission_bps = max_foreman_commission_bps;
+    config.validate()?;
+
+    emit!(ProtocolConfigUpdated {
+        admin: config.admin,
+        treasury: config.treasury,
+        protocol_fee_bps,
+        max_participants,
+        max_cycles,
+        min_cycle_duration,
+        max_foreman_commission_bps,
+    });
+
+    Ok(())
+}
+
+pub fn set_protocol_admin(ctx: Context<UpdateProtocolConfig>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+
+    config.admin = new_admin;
+
+    emit!(ProtocolAdminChanged {
+        previous_admin,
+        new_admin,
+    });
+
+    Ok(())
+}
+
+// Funds already running keep the treasury they were created with
+pub fn set_protocol_treasury(ctx: Context<UpdateProtocolConfig>, treasury: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_treasury = config.treasury;
+
+    config.treasury = treasury;
+
+    emit!(ProtocolTreasuryChanged {
+        previous_treasury,
+        treasury,
+    });
+
+    Ok(())
+}
+
+// Funds already running are not re-checked
+pub fn set_protocol_mint_policy(
+    ctx: Context<UpdateProtocolConfig>,
+    allow_freeze_authority: bool,
+    allow_permanent_delegate: bool,
+    allow_transfer_hook: bool,
+    allow_transfer_fee: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.allow_freeze_authority = allow_freeze_authority;
+    config.allow_permanent_delegate = allow_permanent_delegate;
+    config.allow_transfer_hook = allow_transfer_hook;
+    config.allow_transfer_fee = allow_transfer_fee;
+
+    emit!(MintPolicyUpdated {
+        allow_freeze_authority,
+        allow_permanent_delegate,
+        allow_transfer_hook,
+        allow_transfer_fee,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateMintAllowlist<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = config.bump,
+        has_one = admin @ ChitFundError::Unauthorized,
+    )]
+    pub config: Box<Account<'info, ProtocolConfig>>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+pub fn add_allowed_mint(ctx: Context<UpdateMintAllowlist>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let mint = ctx.accounts.mint.key();
+
+    require!(!config.is_mint_allowed(&mint), ChitFundError::MintAlreadyAllowed);
+    require!(
+        config.allowed_mints.len() < MAX_ALLOWED_MINTS,
+        ChitFundError::AllowlistFull
+    );
+
+    config.allowed_mints.push(mint);
+
+    emit!(MintAllowlistUpdated {
+        mint,
+        allowed: true,
+    });
+
+    Ok(())
+}
+
+pub fn remove_allowed_mint(ctx: Context<UpdateMintAllowlist>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let mint = ctx.accounts.mint.key();
+
+    require!(config.is_mint_allowed(&mint), ChitFundError::MintNotAllowed);
+
+    config.allowed_mints.retain(|allowed| allowed != &mint);
+
+    emit!(MintAllowlistUpdated {
+        mint,
+        allowed: false,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ProtocolConfigUpdated {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub max_participants: u8,
+    pub max_cycles: u8,
+    pub min_cycle_duration: i64,
+    pub max_foreman_commission_bps: u16,
+}
+
+#[event]
+pub struct ProtocolAdminChanged {
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct ProtocolTreasuryChanged {
+    pub previous_treasury: Pubkey,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct MintPolicyUpdated {
+    pub allow_freeze_authority: bool,
+    pub allow_permanent_delegate: bool,
+    pub allow_transfer_hook: bool,
+    pub allow_transfer_fee: bool,
+}
+
+#[event]
+pub struct MintAllowlistUpdated {
+    pub mint: Pubkey,
+    pub allowed: bool,
+}
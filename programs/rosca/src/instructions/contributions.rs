@@ -4,6 +4,9 @@ use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, Tran
 
 use crate::state::{ChitFund, Participant};
 use crate::error::*;
+use crate::extensions::gross_up_for_fee;
+use crate::native::{is_native_mint, wrap_lamports};
+use crate::vault::VaultSigner;
 
 #[derive(Accounts)]
 pub struct MakeContribution<'info> {
@@ -14,15 +17,16 @@ pub struct MakeContribution<'info> {
 
     #[account(
         mut,
-        seeds = [mint.key().as_ref()],
-        bump,
-        constraint = chit_fund.is_active @ ChitFundError::ChitFundInactive,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
     )]
     pub chit_fund: Box<Account<'info, ChitFund>>,
 
     #[account(
         mut,
-        seeds = [b"contribution_vault", mint.key().as_ref()],
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
         bump,
         constraint = contribution_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
     )]
@@ -30,13 +34,15 @@ pub struct MakeContribution<'info> {
 
     #[account(
         mut, 
-        seeds = [user.key().as_ref()],
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
         bump,
         constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
         constraint = !participant.contributions[chit_fund.current_cycle as usize] @ ChitFundError::ContributionAlreadyMade,
     )]
     pub participant: Box<Account<'info, Participant>>,
 
+    // Left out on a native SOL fund to pay lamports from the wallet
     #[account(
         mut,
         associated_token::mint = mint,
@@ -44,7 +50,7 @@ pub struct MakeContribution<'info> {
         associated_token::token_program = token_program,
         constraint = user_token_account.amount >= chit_fund.contribution_amount @ ChitFundError::InsufficientFunds,
     )]
-    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub user_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
@@ -53,39 +59,86 @@ pub struct MakeContribution<'info> {
 pub fn make_contribution(ctx: Context<MakeContribution>) -> Result<()> {
     let chit_fund = &mut ctx.accounts.chit_fund;
     let participant = &mut ctx.accounts.participant;
-    
-    // Transfer contribution amount
-    let transfer_cpi_accounts = TransferChecked {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.contribution_vault.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-    };
-
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(
-        cpi_program,
-        transfer_cpi_accounts
-    );
+
+    let current_time = Clock::get()?.unix_timestamp;
+
+    // Payments after the deadline carry a late fee, shared as a dividend among every other
+    // member, whether or not they paid on time themselves
+    let is_late = current_time > chit_fund.contribution_deadline();
+    let late_fee = if is_late { chit_fund.late_fee() } else { 0 };
+    let payment_amount = chit_fund.contribution_amount + late_fee;
+
+    // With a transfer-fee mint the member covers the fee so the full payment reaches the pot
+    let gross_amount = gross_up_for_fee(&ctx.accounts.mint.to_account_info(), payment_amount)?;
+    let transfer_fee = gross_amount - payment_amount;
+
+    // Each contribution releases the same amount of any payout held back from this member
+    let released = participant.payout_escrow.min(chit_fund.contribution_amount);
 
     let decimals = ctx.accounts.mint.decimals;
-    token_interface::transfer_checked(cpi_ctx, chit_fund.contribution_amount, decimals)?;
+
+    // The member bears any transfer fee on escrow released back to them
+    let mut escrow_transfer_fee = 0;
+    if let Some(user_token_account) = &ctx.accounts.user_token_account {
+        require!(
+            user_token_account.amount >= gross_amount,
+            ChitFundError::InsufficientFunds
+        );
+
+        // Transfer contribution amount
+        let transfer_cpi_accounts = TransferChecked {
+            from: user_token_account.to_account_info(),
+            to: ctx.accounts.contribution_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(
+            cpi_program,
+            transfer_cpi_accounts
+        );
+
+        token_interface::transfer_checked(cpi_ctx, gross_amount, decimals)?;
+
+        if released > 0 {
+            escrow_transfer_fee = VaultSigner::contribution(
+                &ctx.accounts.contribution_vault,
+                &ctx.accounts.mint,
+                &ctx.accounts.token_program,
+                chit_fund.key(),
+                ctx.bumps.contribution_vault,
+            ).transfer(user_token_account.to_account_info(), released)?;
+        }
+    } else {
+        // Paying in SOL, the released escrow is simply netted off what is wrapped
+        require!(is_native_mint(&ctx.accounts.mint.key()), ChitFundError::TokenAccountRequired);
+        wrap_lamports(
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.contribution_vault.to_account_info(),
+            payment_amount - released,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+        )?;
+    }
+
+    participant.payout_escrow -= released;
+    participant.payout_received += released;
+    chit_fund.total_contribution_amount -= released;
+
+    // A liquidated member paying their own way gets this cycle's reserve share back
+    participant.release_reserve_share(chit_fund);
 
     // Update state after successful transfer
     participant.contributions[chit_fund.current_cycle as usize] = true;
-    participant.last_contribution_time = Clock::get()?.unix_timestamp;
+    participant.last_contribution_time = current_time;
     
     // Update chit fund state
-
-
-    // Verify mint and update state
-    match ctx.accounts.mint.to_account_info().key() {
-        key if key == participant.usdc_address => {
-            chit_fund.total_contribution_amount += chit_fund.contribution_amount;
-            participant.total_contributed += chit_fund.contribution_amount;
-        }
-        _ => return Err(ChitFundError::InvalidContributionMint.into())
-    }
+    chit_fund.total_contribution_amount += payment_amount;
+    chit_fund.late_fees_collected += late_fee;
+    participant.dividend_excluded += chit_fund.credit_dividend(late_fee);
+    participant.total_contributed += chit_fund.contribution_amount;
+    chit_fund.mark_paid(participant.owner);
 
     emit!(ContributionMade {
         chit_fund: chit_fund.key(),
@@ -93,6 +146,11 @@ pub fn make_contribution(ctx: Context<MakeContribution>) -> Result<()> {
         cycle: chit_fund.current_cycle,
         amount: chit_fund.contribution_amount,
         contribution_time: participant.last_contribution_time,
+        is_late,
+        late_fee,
+        escrow_released: released,
+        transfer_fee,
+        escrow_transfer_fee,
     });
 
     Ok(())
@@ -105,4 +163,9 @@ pub struct ContributionMade {
     pub cycle: u8,                   // Current cycle number
     pub amount: u64,                 // Contribution amount
     pub contribution_time: i64,      // When the contribution was made
+    pub is_late: bool,               // Paid after the contribution deadline
+    pub late_fee: u64,               // Fee charged on top of the contribution
+    pub escrow_released: u64,        // Held-back payout returned to the member
+    pub transfer_fee: u64,           // Withheld by the mint on top of the payment
+    pub escrow_transfer_fee: u64,    // Withheld by the mint from the escrow released
 }
\ No newline at end of file
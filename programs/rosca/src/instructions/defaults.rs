@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{ChitFund, Participant};
+use crate::error::*;
+use crate::oracle::load_collateral_price;
+
+#[derive(Accounts)]
+pub struct MarkDefault<'info> {
+    pub user: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = Clock::get()?.unix_timestamp >= chit_fund.grace_end_time() @ ChitFundError::ContributionWindowOpen,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    // The member who missed the cycle, not the caller
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), participant.owner.as_ref()],
+        bump,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = !participant.contributions[chit_fund.current_cycle as usize] @ ChitFundError::ContributionAlreadyMade,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    /// CHECK: Must be the feed stored at init; read through the oracle adapters
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+pub fn mark_participant_default(ctx: Context<MarkDefault>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    let amount = chit_fund.contribution_amount;
+
+    // A payout held back from this member covers the cycle first; any remainder comes
+    // from the reserve set aside at liquidation, or else from collateral at the feed price
+    let from_escrow = participant.payout_escrow.min(amount);
+    let uncovered = amount - from_escrow;
+    let from_reserve = participant.liquidation_reserve > 0;
+    let needed = if uncovered == 0 {
+        0
+    } else if from_reserve {
+        participant.release_reserve_share(chit_fund)
+    } else {
+        let collateral_price = load_collateral_price(
+            chit_fund,
+            ctx.accounts.price_feed.as_deref(),
+            ctx.accounts.mint.decimals,
+            ctx.accounts.collateral_mint.decimals,
+        )?;
+        collateral_price.amount_for(uncovered)
+    };
+
+    // Whatever collateral is left is taken and the part of the cycle it cannot cover is
+    // recorded against the member, so the cycle still completes
+    let seized = needed.min(participant.remaining_collateral());
+    let shortfall = if seized < needed {
+        ((uncovered as u128) * ((needed - seized) as u128) / (needed as u128)) as u64
+    } else {
+        0
+    };
+
+    // Escrowed tokens are already in the contribution vault
+    participant.payout_escrow -= from_escrow;
+    participant.payout_received += from_escrow;
+
+    // Seized collateral stays in the collateral vault and is paid to this cycle's
+    // borrower in kind alongside the pot
+    chit_fund.seized_collateral += seized;
+    chit_fund.pot_shortfall += uncovered;
+
+    // Record the default; the cycle now counts as paid
+    let cycle = chit_fund.current_cycle as usize;
+    participant.contributions[cycle] = true;
+    participant.defaults[cycle] = true;
+    participant.collateral_slashed += seized;
+    participant.default_shortfall += shortfall;
+    participant.total_contributed += amount;
+    if !from_reserve {
+        participant.default_count += 1;
+        chit_fund.default_count += 1;
+    }
+    chit_fund.mark_paid(participant.owner);
+
+    emit!(ContributionDefaulted {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        cycle: chit_fund.current_cycle,
+        amount,
+        collateral_seized: seized,
+        shortfall,
+        collateral_remaining: participant.remaining_collateral(),
+        default_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct ContributionDefaulted {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub cycle: u8,
+    pub amount: u64,
+    pub collateral_seized: u64,
+    pub shortfall: u64,
+    pub collateral_remaining: u64,
+    pub default_time: i64,
+}
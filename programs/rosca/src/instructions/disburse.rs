@@ -1,9 +1,10 @@
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token_interface::{self,Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::{ChitFund, Participant};
+use crate::state::{ChitFund, Participant, SelectionMode};
 use crate::error::*;
+use crate::payout::{pay_out_pot, PotAccounts};
+use crate::vault::VaultSigner;
 
 
 #[derive(Accounts)]
@@ -13,43 +14,88 @@ pub struct DisburseFunds<'info> {
 
     pub mint: InterfaceAccount<'info, Mint>,
 
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
-        seeds = [mint.key().as_ref()],
-        bump,
-        constraint = chit_fund.is_active @ ChitFundError::ChitFundInactive,
-        constraint = Clock::get()?.unix_timestamp >= chit_fund.last_disbursement_time + chit_fund.cycle_duration @ ChitFundError::CycleNotComplete,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode != SelectionMode::Auction @ ChitFundError::AuctionFund,
+        constraint = Clock::get()?.unix_timestamp >= chit_fund.cycle_end_time() @ ChitFundError::CycleNotComplete,
+        constraint = chit_fund.payout_recipient() != Pubkey::default() @ ChitFundError::WinnerNotDrawn,
+        constraint = chit_fund.all_contributions_in() @ ChitFundError::PendingContributions,
     )]
     pub chit_fund: Box<Account<'info, ChitFund>>,
     
     #[account(
         mut,
-        seeds = [b"contribution_vault", mint.key().as_ref()],
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
         bump,
         constraint = contribution_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
     )]
     pub contribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // This cycle's drawn, voted or scheduled borrower, whoever signs the transaction
     #[account(
         mut, 
-        seeds = [user.key().as_ref()],
+        seeds = [b"participant", chit_fund.key().as_ref(), chit_fund.payout_recipient().as_ref()],
         bump,
-        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
         constraint = !participant.has_borrowed @ ChitFundError::AlreadyBorrowed,
     )]
     pub participant: Box<Account<'info, Participant>>,
 
+    // Left out when the borrower is paid in SOL
+    #[account(
+        mut,
+        constraint = borrower_token_account.key() == participant.usdc_address @ ChitFundError::InvalidBorrowerAccount,
+    )]
+    pub borrower_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        constraint = borrower_collateral_account.key() == participant.collateral_address @ ChitFundError::InvalidBorrowerAccount,
+    )]
+    pub borrower_collateral_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Needed when the borrower joined without a token account and is paid in SOL
+    #[account(mut, address = participant.owner @ ChitFundError::InvalidBorrowerAccount)]
+    pub borrower_wallet: Option<SystemAccount<'info>>,
+
+    /// CHECK: Created, filled and closed within this instruction to unwrap SOL
+    #[account(mut, seeds = [b"unwrap", chit_fund.key().as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    // Left out when the fund charges no commission
+    #[account(
+        mut,
+        constraint = creator_fee_account.owner == chit_fund.creator @ ChitFundError::InvalidCreatorFeeAccount,
+        constraint = creator_fee_account.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub creator_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    // Left out when the fund charges no protocol fee
     #[account(
         mut,
-        associated_token::mint = mint,
-        associated_token::authority = user,
-        associated_token::token_program = token_program,
+        constraint = treasury_fee_account.owner == chit_fund.protocol_treasury @ ChitFundError::InvalidTreasuryAccount,
+        constraint = treasury_fee_account.mint == mint.key() @ ChitFundError::InvalidContributionMint,
     )]
-    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub treasury_fee_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 pub fn disburse_funds(ctx: Context<DisburseFunds>) -> Result<()> {
     let chit_fund = &mut ctx.accounts.chit_fund;
@@ -57,103 +103,54 @@ pub fn disburse_funds(ctx: Context<DisburseFunds>) -> Result<()> {
     
     let current_time = Clock::get()?.unix_timestamp;
     
-    // Check if there are any participants
-    require!(
-        chit_fund.participants_count > 0, 
-        ChitFundError::ParticipantNotFound  
-    );
-
-    // Create vector to store eligible borrowers
-    let mut eligible_borrowers: Vec<Pubkey> = Vec::new();
-    
-    // Check each participant in the chit fund
-    for i in 0..chit_fund.participants_count as usize {
-        let current_pubkey = chit_fund.participants[i];
-        
-        // Add to eligible list if they haven't borrowed yet
-        if current_pubkey != Pubkey::default() && !chit_fund.borrowed_participants[i] {
-            eligible_borrowers.push(current_pubkey);
-        }
-    }
-
-    // Ensure there are eligible borrowers
-    require!(
-        !eligible_borrowers.is_empty(),
-        ChitFundError::NoEligibleBorrowers
-    );
-
-    // Select random borrower using current timestamp
-    let random_index = current_time as usize % eligible_borrowers.len();
-    let selected_borrower = eligible_borrowers[random_index];
-
     // Verify selected borrower
     require!(
-        participant.owner == selected_borrower,
+        participant.owner == chit_fund.payout_recipient(),
         ChitFundError::InvalidBorrowerAccount
     );
 
     // Get disbursement amount
     let disbursement_amount = chit_fund.disbursement_schedule[chit_fund.current_cycle as usize];
 
-    // Transfer funds
-    let transfer_cpi_accounts = TransferChecked {
-        from: ctx.accounts.contribution_vault.to_account_info(),
-        to: ctx.accounts.user_token_account.to_account_info(),
-        authority: ctx.accounts.contribution_vault.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
+    let chit_fund_key = chit_fund.key();
+    let pot_accounts = PotAccounts {
+        payer: ctx.accounts.user.to_account_info(),
+        contribution_vault: VaultSigner::contribution(
+            &ctx.accounts.contribution_vault,
+            &ctx.accounts.mint,
+            &ctx.accounts.token_program,
+            chit_fund_key,
+            ctx.bumps.contribution_vault,
+        ),
+        collateral_vault: VaultSigner::collateral(
+            &ctx.accounts.collateral_vault,
+            &ctx.accounts.collateral_mint,
+            &ctx.accounts.collateral_token_program,
+            chit_fund_key,
+            ctx.bumps.collateral_vault,
+        ),
+        recipient_token_account: ctx.accounts.borrower_token_account.as_ref().map(|account| account.to_account_info()),
+        recipient_collateral_account: ctx.accounts.borrower_collateral_account.as_ref().map(|account| account.to_account_info()),
+        recipient_wallet: ctx.accounts.borrower_wallet.as_ref().map(|account| account.to_account_info()),
+        unwrap_account: ctx.accounts.unwrap_account.as_ref().map(|account| account.to_account_info()),
+        unwrap_bump: ctx.bumps.unwrap_account.unwrap_or_default(),
+        creator_fee_account: ctx.accounts.creator_fee_account.as_ref().map(|account| account.to_account_info()),
+        treasury_fee_account: ctx.accounts.treasury_fee_account.as_ref().map(|account| account.to_account_info()),
+        system_program: ctx.accounts.system_program.to_account_info(),
     };
-
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-
-    let mint_key = ctx.accounts.mint.key();
-
-    let signer_seeds: &[&[&[u8]]] = &[
-        &[
-            b"contribution_vault",
-            mint_key.as_ref(),
-            &[ctx.bumps.contribution_vault]
-            ]];
-
-    let cpi_ctx = CpiContext::new(cpi_program,transfer_cpi_accounts).with_signer(signer_seeds);
-
-    let decimals = ctx.accounts.mint.decimals;
-
-    token_interface::transfer_checked(cpi_ctx, disbursement_amount, decimals)?;
-
-    // Update borrowed status in chit fund
-    for i in 0..chit_fund.participants_count as usize {
-        if chit_fund.participants[i] == participant.owner {
-            chit_fund.borrowed_participants[i] = true;
-            break;
-        }
-    }
-
-    // Update participant state
-    participant.has_borrowed = true;
-    participant.borrowed_cycle = Some(chit_fund.current_cycle);
+    let payout = pay_out_pot(pot_accounts, chit_fund, participant, disbursement_amount)?;
 
     // Update chit fund state
-    chit_fund.current_cycle += 1;
-    chit_fund.last_disbursement_time = current_time;
-
-    // Check if chit fund is complete
-    if chit_fund.current_cycle == chit_fund.total_cycles {
-        chit_fund.is_active = false;
-    }
-
-    // Update token amounts based on mint verification
-    match ctx.accounts.mint.key() {
-        key if key == participant.usdc_address => {
-            chit_fund.total_contribution_amount -= disbursement_amount;
-            participant.total_contributed -= disbursement_amount;
-        }
-        _ => return Err(ChitFundError::InvalidContributionMint.into())
-    }
+    chit_fund.advance_cycle(current_time);
 
     emit!(FundsDisbursed {
-        chit_fund: chit_fund.key(),
+        chit_fund: chit_fund_key,
         participant: participant.key(),
         amount: disbursement_amount,
+        collateral_amount: payout.collateral_amount,
+        held_back: payout.held_back,
+        transfer_fee: payout.transfer_fee,
+        collateral_transfer_fee: payout.collateral_transfer_fee,
         cycle: chit_fund.current_cycle - 1,
         disbursement_time: current_time,
     });
@@ -166,6 +163,10 @@ pub struct FundsDisbursed {
     pub chit_fund: Pubkey,
     pub participant: Pubkey,
     pub amount: u64,
+    pub collateral_amount: u64,
+    pub held_back: u64,
+    pub transfer_fee: u64,
+    pub collateral_transfer_fee: u64,
     pub cycle: u8,
     pub disbursement_time: i64,
-}
\ No newline at end of file
+}
@@ -0,0 +1,225 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::state::{ChitFund, DissolveVote, FundStatus, Participant};
+use crate::error::*;
+use crate::oracle::load_collateral_price;
+
+#[derive(Accounts)]
+pub struct VoteDissolve<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    // One vote per member
+    #[account(
+        init,
+        payer = user,
+        space = 8 + DissolveVote::INIT_SPACE,
+        seeds = [b"dissolve_vote", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+    )]
+    pub vote: Box<Account<'info, DissolveVote>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn vote_dissolve_fund(ctx: Context<VoteDissolve>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let vote = &mut ctx.accounts.vote;
+
+    vote.chit_fund = chit_fund.key();
+    vote.voter = ctx.accounts.user.key();
+    chit_fund.dissolve_votes += 1;
+
+    emit!(DissolveVoteCast {
+        chit_fund: chit_fund.key(),
+        voter: vote.voter,
+        votes: chit_fund.dissolve_votes,
+        passed: chit_fund.dissolve_vote_passed(),
+    });
+
+    Ok(())
+}
+
+// Every Participant account is passed through remaining_accounts, writable and in roster order
+#[derive(Accounts)]
+pub struct DissolveFund<'info> {
+    pub user: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    /// CHECK: Must be the feed stored at init; read through the oracle adapters
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+pub fn dissolve_chit_fund<'info>(ctx: Context<'_, '_, 'info, 'info, DissolveFund<'info>>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let is_creator = ctx.accounts.user.key() == chit_fund.creator;
+    require!(
+        is_creator || chit_fund.dissolve_vote_passed() || chit_fund.too_many_defaults(),
+        ChitFundError::CannotDissolveFund
+    );
+
+    require!(
+        ctx.remaining_accounts.len() == chit_fund.participants_count as usize,
+        ChitFundError::InvalidParticipantAccounts
+    );
+
+    // Load the roster
+    let mut participants: Vec<Account<'info, Participant>> = Vec::new();
+    for (i, account_info) in ctx.remaining_accounts.iter().enumerate() {
+        let mut participant = Account::<Participant>::try_from(account_info)?;
+        require!(
+            participant.chit_fund == chit_fund.key() && participant.owner == chit_fund.participants[i],
+            ChitFundError::InvalidParticipantAccounts
+        );
+        // Any liquidation reserve goes back into the member's collateral and any held-back
+        // payout joins the pot before settling
+        participant.liquidation_reserve = 0;
+        participant.payout_escrow = 0;
+        participants.push(participant);
+    }
+
+    let collateral_price = load_collateral_price(
+        chit_fund,
+        ctx.accounts.price_feed.as_deref(),
+        ctx.accounts.mint.decimals,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+
+    // Members who are behind settle from collateral, members who are ahead share the pot and
+    // the seized collateral pro rata.
+    let mut net_positions: Vec<i128> = Vec::new();
+    let mut total_credit: u128 = 0;
+    let mut collateral_seized: Vec<u64> = Vec::new();
+    let mut total_seized: u64 = 0;
+
+    for participant in participants.iter() {
+        let net = participant.net_position(chit_fund);
+
+        let seized = if net < 0 {
+            let owed = net.unsigned_abs() as u64;
+            collateral_price.amount_for(owed).min(participant.remaining_collateral())
+        } else {
+            total_credit += net as u128;
+            0
+        };
+
+        net_positions.push(net);
+        collateral_seized.push(seized);
+        total_seized += seized;
+    }
+
+    // The pot is what the fund's ledger holds rather than the vault balance, which anyone can
+    // add to. Seized collateral stays in the collateral vault and is shared in kind, together
+    // with anything seized from defaulters this cycle that has not been paid out yet.
+    let chit_fund_key = chit_fund.key();
+    let available_pot = chit_fund.total_contribution_amount as u128;
+    let available_collateral = chit_fund.seized_collateral as u128 + total_seized as u128;
+
+    for (i, participant) in participants.iter_mut().enumerate() {
+        let net = net_positions[i];
+        let (settlement_amount, settlement_collateral) = if net > 0 && total_credit > 0 {
+            (
+                (available_pot * net as u128 / total_credit) as u64,
+                (available_collateral * net as u128 / total_credit) as u64,
+            )
+        } else {
+            (0, 0)
+        };
+
+        // Unclaimed dividends are folded into the settlement
+        participant.dividends_claimed += participant.claimable_dividend(chit_fund);
+        participant.collateral_slashed += collateral_seized[i];
+        participant.settlement_amount = settlement_amount;
+        participant.settlement_collateral = settlement_collateral;
+
+        emit!(SettlementStatement {
+            chit_fund: chit_fund_key,
+            participant: participant.key(),
+            contributed: participant.pot_contributions(chit_fund),
+            received: participant.payout_received,
+            borrowed_cycle: participant.borrowed_cycle,
+            collateral_seized: collateral_seized[i],
+            settlement_amount,
+            settlement_collateral,
+            collateral_refund: participant.remaining_collateral(),
+        });
+
+        participant.exit(&crate::ID)?;
+    }
+
+    chit_fund.seized_collateral = 0;
+    chit_fund.pot_shortfall = 0;
+    chit_fund.status = FundStatus::Dissolved;
+
+    emit!(ChitFundDissolved {
+        chit_fund: chit_fund_key,
+        dissolved_by: ctx.accounts.user.key(),
+        cycle: chit_fund.current_cycle,
+        collateral_seized: total_seized,
+        dissolve_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct DissolveVoteCast {
+    pub chit_fund: Pubkey,
+    pub voter: Pubkey,
+    pub votes: u8,
+    pub passed: bool,
+}
+
+#[event]
+pub struct SettlementStatement {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub contributed: u64,
+    pub received: u64,
+    pub borrowed_cycle: Option<u8>,
+    pub collateral_seized: u64,
+    pub settlement_amount: u64,
+    pub settlement_collateral: u64,
+    pub collateral_refund: u64,
+}
+
+#[event]
+pub struct ChitFundDissolved {
+    pub chit_fund: Pubkey,
+    pub dissolved_by: Pubkey,
+    pub cycle: u8,
+    pub collateral_seized: u64,
+    pub dissolve_time: i64,
+}
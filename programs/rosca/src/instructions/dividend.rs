@@ -0,0 +1,125 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::state::{ChitFund, Participant};
+use crate::error::*;
+use crate::native::{is_native_mint, Unwrap};
+use crate::vault::VaultSigner;
+
+#[derive(Accounts)]
+pub struct ClaimDividend<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"contribution_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = contribution_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+    )]
+    pub contribution_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    // Left out on a native SOL fund to claim straight to the wallet
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = user,
+        associated_token::token_program = token_program,
+    )]
+    pub user_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Created, filled and closed within this instruction to unwrap SOL
+    #[account(mut, seeds = [b"unwrap", chit_fund.key().as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn claim_member_dividend(ctx: Context<ClaimDividend>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    let amount = participant.claimable_dividend(chit_fund);
+    require!(amount > 0, ChitFundError::NoDividendAvailable);
+
+    let chit_fund_key = chit_fund.key();
+
+    // Without a token account the dividend is unwrapped to the wallet as SOL
+    let unwrap = match &ctx.accounts.user_token_account {
+        Some(_) => None,
+        None => {
+            require!(is_native_mint(&ctx.accounts.mint.key()), ChitFundError::TokenAccountRequired);
+            let unwrap_account = ctx.accounts.unwrap_account.as_ref().ok_or(ChitFundError::UnwrapAccountRequired)?;
+            Some(Unwrap::open(
+                unwrap_account.to_account_info(),
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.mint.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                chit_fund_key,
+                ctx.bumps.unwrap_account.unwrap_or_default(),
+            )?)
+        }
+    };
+    let destination = match &unwrap {
+        Some(unwrap) => unwrap.account.clone(),
+        None => ctx.accounts.user_token_account.as_ref().ok_or(ChitFundError::TokenAccountRequired)?.to_account_info(),
+    };
+
+    // Transfer dividend; a transfer-fee mint withholds its fee from the member
+    let transfer_fee = VaultSigner::contribution(
+        &ctx.accounts.contribution_vault,
+        &ctx.accounts.mint,
+        &ctx.accounts.token_program,
+        chit_fund_key,
+        ctx.bumps.contribution_vault,
+    ).transfer(destination, amount)?;
+
+    if let Some(unwrap) = unwrap {
+        unwrap.close(ctx.accounts.user.to_account_info(), amount)?;
+    }
+
+    participant.dividends_claimed += amount;
+    chit_fund.total_contribution_amount -= amount;
+
+    emit!(DividendClaimed {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        amount,
+        transfer_fee,
+        claim_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct DividendClaimed {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub transfer_fee: u64,
+    pub claim_time: i64,
+}
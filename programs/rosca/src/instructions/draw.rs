@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::state::{ChitFund, Participant, SelectionMode};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CommitSeed<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode == SelectionMode::Lottery @ ChitFundError::NotLotteryFund,
+        constraint = Clock::get()?.unix_timestamp < chit_fund.cycle_end_time() @ ChitFundError::CommitPhaseClosed,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = participant.commit_cycle != Some(chit_fund.current_cycle) @ ChitFundError::AlreadyCommitted,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+}
+
+// The commitment is hash(seed || owner), so a member cannot copy someone else's commitment
+pub fn commit_draw_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    participant.seed_commitment = commitment;
+    participant.commit_cycle = Some(chit_fund.current_cycle);
+    participant.has_revealed = false;
+    chit_fund.commit_count += 1;
+    if let Some(index) = chit_fund.participant_index(participant.owner) {
+        chit_fund.unrevealed[index] = true;
+    }
+
+    emit!(SeedCommitted {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        cycle: chit_fund.current_cycle,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevealSeed<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = !chit_fund.has_selected_borrower() @ ChitFundError::WinnerAlreadyDrawn,
+        constraint = Clock::get()?.unix_timestamp >= chit_fund.cycle_end_time() @ ChitFundError::RevealPhaseNotOpen,
+        constraint = Clock::get()?.unix_timestamp < chit_fund.reveal_end_time() @ ChitFundError::RevealPhaseClosed,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = participant.commit_cycle == Some(chit_fund.current_cycle) @ ChitFundError::NoCommitment,
+        constraint = !participant.has_revealed @ ChitFundError::AlreadyRevealed,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+}
+
+pub fn reveal_draw_seed(ctx: Context<RevealSeed>, seed: [u8; 32]) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    let commitment = hashv(&[&seed, participant.owner.as_ref()]).to_bytes();
+    require!(
+        commitment == participant.seed_commitment,
+        ChitFundError::InvalidReveal
+    );
+
+    // Fold the revealed seed into the cycle's draw seed
+    chit_fund.draw_seed = hashv(&[&chit_fund.draw_seed, &seed]).to_bytes();
+    chit_fund.reveal_count += 1;
+    participant.has_revealed = true;
+    if let Some(index) = chit_fund.participant_index(participant.owner) {
+        chit_fund.unrevealed[index] = false;
+    }
+
+    emit!(SeedRevealed {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        cycle: chit_fund.current_cycle,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DrawWinner<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode == SelectionMode::Lottery @ ChitFundError::NotLotteryFund,
+        constraint = !chit_fund.has_selected_borrower() @ ChitFundError::WinnerAlreadyDrawn,
+        constraint = Clock::get()?.unix_timestamp >= chit_fund.cycle_end_time() @ ChitFundError::CycleNotComplete,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    /// CHECK: read raw, the sysvar is too large to deserialize on-chain
+    #[account(address = slot_hashes::ID)]
+    pub recent_slothashes: UncheckedAccount<'info>,
+}
+
+pub fn draw_cycle_winner(ctx: Context<DrawWinner>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    let mut used_slot_hash = false;
+
+    if chit_fund.has_priority_borrower() {
+        // An approved emergency request takes this cycle's pot
+        chit_fund.selected_borrower = chit_fund.priority_borrower;
+    } else {
+        let all_revealed = chit_fund.commit_count > 0 && chit_fund.reveal_count == chit_fund.commit_count;
+        require!(
+            all_revealed || current_time >= chit_fund.reveal_end_time(),
+            ChitFundError::RevealsPending
+        );
+
+        // Members who committed but never revealed are left out of the draw, so withholding
+        // a seed can only cost the member who does it
+        let eligible_borrowers = chit_fund.draw_candidates();
+
+        // Ensure there are eligible borrowers
+        require!(
+            !eligible_borrowers.is_empty(),
+            ChitFundError::NoEligibleBorrowers
+        );
+
+        // Revealed seeds alone decide the draw, so nobody can steer it by choosing when to call.
+        // With no seed revealed at all the seed would be predictable, so the most recent slot
+        // hash is mixed in instead.
+        let mut seed = chit_fund.draw_seed;
+        if chit_fund.reveal_count == 0 {
+            let data = ctx.accounts.recent_slothashes.try_borrow_data()?;
+            // Layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first
+            let recent_hash = data.get(16..48).ok_or(ChitFundError::SlotHashUnavailable)?;
+            seed = hashv(&[&seed, recent_hash]).to_bytes();
+            used_slot_hash = true;
+        }
+
+        let mut index_bytes = [0u8; 8];
+        index_bytes.copy_from_slice(&seed[..8]);
+        let random_index = (u64::from_le_bytes(index_bytes) % eligible_borrowers.len() as u64) as usize;
+        chit_fund.selected_borrower = eligible_borrowers[random_index];
+    }
+
+    emit!(WinnerDrawn {
+        chit_fund: chit_fund.key(),
+        cycle: chit_fund.current_cycle,
+        selected_borrower: chit_fund.selected_borrower,
+        commit_count: chit_fund.commit_count,
+        reveal_count: chit_fund.reveal_count,
+        used_slot_hash,
+        draw_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct SeedCommitted {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub cycle: u8,
+}
+
+#[event]
+pub struct SeedRevealed {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub cycle: u8,
+}
+
+#[event]
+pub struct WinnerDrawn {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub selected_borrower: Pubkey,
+    pub commit_count: u8,
+    pub reveal_count: u8,
+    pub used_slot_hash: bool,
+    pub draw_time: i64,
+}
@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ChitFund, EmergencyVote, Participant};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct RequestEmergency<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = !chit_fund.has_priority_borrower() @ ChitFundError::PriorityBorrowerPending,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = !participant.has_borrowed @ ChitFundError::AlreadyBorrowed,
+        constraint = !participant.is_emergency_requested @ ChitFundError::EmergencyAlreadyRequested,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+}
+
+pub fn request_emergency_payout(ctx: Context<RequestEmergency>, reason_code: u8) -> Result<()> {
+    let participant = &mut ctx.accounts.participant;
+
+    participant.is_emergency_requested = true;
+    participant.emergency_reason = reason_code;
+    participant.emergency_votes_for = 0;
+    participant.emergency_votes_against = 0;
+    participant.emergency_approved = false;
+    participant.emergency_requests = participant
+        .emergency_requests
+        .checked_add(1)
+        .ok_or(ChitFundError::TooManyEmergencyRequests)?;
+
+    emit!(EmergencyRequested {
+        chit_fund: ctx.accounts.chit_fund.key(),
+        participant: participant.key(),
+        reason_code,
+        request_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct VoteEmergency<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = voter_participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = voter_participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+    )]
+    pub voter_participant: Box<Account<'info, Participant>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), requester_participant.owner.as_ref()],
+        bump,
+        constraint = requester_participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = requester_participant.owner != user.key() @ ChitFundError::CannotVoteOwnRequest,
+        constraint = requester_participant.is_emergency_requested @ ChitFundError::NoEmergencyRequest,
+        constraint = !requester_participant.emergency_approved @ ChitFundError::NoEmergencyRequest,
+    )]
+    pub requester_participant: Box<Account<'info, Participant>>,
+
+    // One vote per member per request
+    #[account(
+        init,
+        payer = user,
+        space = 8 + EmergencyVote::INIT_SPACE,
+        seeds = [
+            b"emergency_vote",
+            requester_participant.key().as_ref(),
+            &[requester_participant.emergency_requests],
+            user.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub vote: Box<Account<'info, EmergencyVote>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn vote_emergency_payout(ctx: Context<VoteEmergency>, approve: bool) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let requester = &mut ctx.accounts.requester_participant;
+    let vote = &mut ctx.accounts.vote;
+
+    vote.chit_fund = chit_fund.key();
+    vote.requester = requester.owner;
+    vote.voter = ctx.accounts.user.key();
+    vote.approve = approve;
+
+    if approve {
+        requester.emergency_votes_for += 1;
+    } else {
+        requester.emergency_votes_against += 1;
+    }
+
+    let approved = requester.emergency_vote_passed(chit_fund);
+    let rejected = !approved && requester.emergency_vote_failed(chit_fund);
+
+    let mut queued = false;
+    if approved {
+        // With another approved request still waiting for its pot, this one queues behind it
+        requester.emergency_approved = true;
+        queued = chit_fund.approve_emergency(requester.owner);
+    } else if rejected {
+        // A request voted down is withdrawn so the member can make a new one
+        requester.is_emergency_requested = false;
+    }
+
+    emit!(EmergencyVoteCast {
+        chit_fund: chit_fund.key(),
+        requester: requester.key(),
+        voter: vote.voter,
+        approve,
+        votes_for: requester.emergency_votes_for,
+        votes_against: requester.emergency_votes_against,
+        approved,
+        queued,
+        rejected,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct EmergencyRequested {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub reason_code: u8,
+    pub request_time: i64,
+}
+
+#[event]
+pub struct EmergencyVoteCast {
+    pub chit_fund: Pubkey,
+    pub requester: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+    pub votes_for: u8,
+    pub votes_against: u8,
+    pub approved: bool,
+    pub queued: bool,
+    pub rejected: bool,
+}
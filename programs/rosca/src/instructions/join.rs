@@ -2,9 +2,12 @@ use anchor_lang::prelude::*;
 use anchor_spl::{associated_token::AssociatedToken, 
      token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked}};
 
-use crate::state::{ChitFund, Participant};
+use crate::state::{ChitFund, FundStatus, Participant};
 use crate::constants::*;
 use crate::error::*;
+use crate::extensions::gross_up_for_fee;
+use crate::oracle::load_collateral_price;
+use crate::native::{is_native_mint, wrap_lamports};
 
 #[derive(Accounts)]
 pub struct JoinChitFund<'info> {
@@ -13,21 +16,27 @@ pub struct JoinChitFund<'info> {
 
     pub mint: InterfaceAccount<'info, Mint>,
 
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
     #[account(
         mut,
-        seeds = [mint.key().as_ref()],
-        bump,
-        constraint = chit_fund.is_active @ ChitFundError::ChitFundInactive,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.status == FundStatus::Enrolling @ ChitFundError::EnrollmentClosed,
+        constraint = Clock::get()?.unix_timestamp < chit_fund.enrollment_deadline @ ChitFundError::EnrollmentClosed,
         constraint = chit_fund.participants_count < chit_fund.max_participants @ ChitFundError::MaxParticipantsReached,
+        constraint = chit_fund.participants_count < chit_fund.total_cycles @ ChitFundError::MaxParticipantsReached,
     )]
     pub chit_fund: Box<Account<'info, ChitFund>>,
 
 
     #[account(
         mut,
-        seeds = [b"collateral_vault", mint.key().as_ref()],
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
         bump,
-        constraint = collateral_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
     )]
     pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -35,22 +44,33 @@ pub struct JoinChitFund<'info> {
         init,
         payer = user,
         space = 8 + Participant::INIT_SPACE,
-        seeds = [user.key().as_ref()],
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
         bump
     )]
     pub participant: Box<Account<'info, Participant>>,
 
+    // Left out on a native SOL fund to be paid straight to the wallet
     #[account(
-        mut,
         associated_token::mint = mint,
         associated_token::authority = user,
         associated_token::token_program = token_program,
-        constraint = user_token_account.amount >= chit_fund.collateral_requirement @ ChitFundError::InsufficientCollateral,
     )]
-    pub user_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub user_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
+    // Left out on native SOL collateral to post lamports from the wallet
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = user,
+        associated_token::token_program = collateral_token_program,
+    )]
+    pub user_collateral_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: Must be the feed stored at init; read through the oracle adapters
+    pub price_feed: Option<UncheckedAccount<'info>>,
 
     pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 
@@ -61,16 +81,60 @@ pub fn join_chit_fund(ctx: Context<JoinChitFund>) -> Result<()> {
     let chit_fund = &mut ctx.accounts.chit_fund;
     let participant = &mut ctx.accounts.participant;
 
+    // Post collateral worth the requirement at the current price
+    let collateral_price = load_collateral_price(
+        chit_fund,
+        ctx.accounts.price_feed.as_deref(),
+        ctx.accounts.mint.decimals,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+    let collateral_amount = collateral_price.amount_for(chit_fund.collateral_requirement);
+
+    // The member covers any transfer fee so the full amount reaches the vault
+    let gross_amount = gross_up_for_fee(&ctx.accounts.collateral_mint.to_account_info(), collateral_amount)?;
+    if let Some(user_collateral_account) = &ctx.accounts.user_collateral_account {
+        require!(
+            user_collateral_account.amount >= gross_amount,
+            ChitFundError::InsufficientCollateral
+        );
+    }
+
+    // Without a token account, payouts go to the wallet as SOL
+    let usdc_address = match &ctx.accounts.user_token_account {
+        Some(user_token_account) => user_token_account.key(),
+        None => {
+            require!(is_native_mint(&ctx.accounts.mint.key()), ChitFundError::TokenAccountRequired);
+            ctx.accounts.user.key()
+        }
+    };
+    let collateral_address = match &ctx.accounts.user_collateral_account {
+        Some(user_collateral_account) => user_collateral_account.key(),
+        None => {
+            require!(is_native_mint(&ctx.accounts.collateral_mint.key()), ChitFundError::TokenAccountRequired);
+            ctx.accounts.user.key()
+        }
+    };
+
     // Update participant data
     // Identity
     participant.owner = ctx.accounts.user.key();
     participant.chit_fund = chit_fund.key();
-    participant.usdc_address = ctx.accounts.user_token_account.key();
+    participant.usdc_address = usdc_address;
+    participant.collateral_address = collateral_address;
 
     // State tracking
     participant.has_borrowed = false;
     participant.is_emergency_requested = false;
+    participant.emergency_reason = 0;
+    participant.emergency_votes_for = 0;
+    participant.emergency_votes_against = 0;
+    participant.emergency_approved = false;
+    participant.emergency_requests = 0;
     participant.contributions = [false; MAX_CYCLES];
+    participant.defaults = [false; MAX_CYCLES];
+    participant.seed_commitment = [0; 32];
+    participant.commit_cycle = None;
+    participant.has_revealed = false;
 
     // Time tracking
     participant.join_time = Clock::get()?.unix_timestamp;
@@ -79,6 +143,20 @@ pub fn join_chit_fund(ctx: Context<JoinChitFund>) -> Result<()> {
     // Financial tracking
     participant.total_contributed = 0;
     participant.borrowed_cycle = None;
+    participant.dividends_claimed = 0;
+    participant.dividend_excluded = chit_fund.dividend_per_member;
+    participant.default_count = 0;
+    participant.collateral_deposited = 0;
+    participant.collateral_slashed = 0;
+    participant.collateral_returned = 0;
+    participant.is_refunded = false;
+    participant.payout_received = 0;
+    participant.settlement_amount = 0;
+    participant.settlement_collateral = 0;
+    participant.is_liquidated = false;
+    participant.liquidation_reserve = 0;
+    participant.payout_escrow = 0;
+    participant.default_shortfall = 0;
 
     // Update chit fund participants list
     let current_count = chit_fund.participants_count as usize;
@@ -86,37 +164,42 @@ pub fn join_chit_fund(ctx: Context<JoinChitFund>) -> Result<()> {
     chit_fund.participants_count += 1;
 
     // Transfer collateral
-    let transfer_cpi_accounts = TransferChecked {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.collateral_vault.to_account_info(),
-        authority: ctx.accounts.user.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
-    };
-
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(
-        cpi_program,
-        transfer_cpi_accounts
-    );
-
-    let decimals = ctx.accounts.mint.decimals;
-    token_interface::transfer_checked(cpi_ctx, chit_fund.collateral_requirement,decimals)?;
-
-    match ctx.accounts.mint.to_account_info().key(){
-        key if key == participant.usdc_address =>{
-            chit_fund.total_contribution_amount += chit_fund.collateral_requirement;
-            participant.total_contributed += chit_fund.collateral_requirement;
-
-        }
-        _ => return Err(ChitFundError::InvalidContributionMint.into())
+    if let Some(user_collateral_account) = &ctx.accounts.user_collateral_account {
+        let transfer_cpi_accounts = TransferChecked {
+            from: user_collateral_account.to_account_info(),
+            to: ctx.accounts.collateral_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+            mint: ctx.accounts.collateral_mint.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.collateral_token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(
+            cpi_program,
+            transfer_cpi_accounts
+        );
+
+        let decimals = ctx.accounts.collateral_mint.decimals;
+        token_interface::transfer_checked(cpi_ctx, gross_amount,decimals)?;
+    } else {
+        wrap_lamports(
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.collateral_vault.to_account_info(),
+            collateral_amount,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.collateral_token_program.to_account_info(),
+        )?;
     }
 
+    // Collateral is tracked apart from contributions
+    chit_fund.total_collateral_amount += collateral_amount;
+    participant.collateral_deposited += collateral_amount;
+
     emit!(ParticipantJoined {
         chit_fund: chit_fund.key(),
         participant: participant.key(),
         owner: participant.owner,
         join_time: participant.join_time,
-        collateral_amount: chit_fund.collateral_requirement, 
+        collateral_amount,
     });
 
     Ok(())
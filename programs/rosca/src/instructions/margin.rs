@@ -0,0 +1,245 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::state::{ChitFund, Participant};
+use crate::constants::*;
+use crate::error::*;
+use crate::extensions::gross_up_for_fee;
+use crate::oracle::load_collateral_price;
+use crate::native::{is_native_mint, wrap_lamports};
+use crate::vault::VaultSigner;
+
+#[derive(Accounts)]
+pub struct TopUpCollateral<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = !chit_fund.has_ended() @ ChitFundError::ChitFundInactive,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    // Left out on native SOL collateral to top up from the wallet
+    #[account(
+        mut,
+        associated_token::mint = collateral_mint,
+        associated_token::authority = user,
+        associated_token::token_program = collateral_token_program,
+    )]
+    pub user_collateral_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+pub fn top_up_participant_collateral(ctx: Context<TopUpCollateral>, amount: u64) -> Result<()> {
+    require!(amount > 0, ChitFundError::InvalidCollateralAmount);
+
+    // Transfer collateral
+    if let Some(user_collateral_account) = &ctx.accounts.user_collateral_account {
+        // The member covers any transfer fee so the full amount reaches the vault
+        let gross_amount = gross_up_for_fee(&ctx.accounts.collateral_mint.to_account_info(), amount)?;
+        require!(
+            user_collateral_account.amount >= gross_amount,
+            ChitFundError::InsufficientCollateral
+        );
+
+        let transfer_cpi_accounts = TransferChecked {
+            from: user_collateral_account.to_account_info(),
+            to: ctx.accounts.collateral_vault.to_account_info(),
+            authority: ctx.accounts.user.to_account_info(),
+            mint: ctx.accounts.collateral_mint.to_account_info(),
+        };
+
+        let cpi_program = ctx.accounts.collateral_token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, transfer_cpi_accounts);
+
+        let decimals = ctx.accounts.collateral_mint.decimals;
+        token_interface::transfer_checked(cpi_ctx, gross_amount, decimals)?;
+    } else {
+        require!(is_native_mint(&ctx.accounts.collateral_mint.key()), ChitFundError::TokenAccountRequired);
+        wrap_lamports(
+            ctx.accounts.user.to_account_info(),
+            ctx.accounts.collateral_vault.to_account_info(),
+            amount,
+            ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.collateral_token_program.to_account_info(),
+        )?;
+    }
+
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    chit_fund.total_collateral_amount += amount;
+    participant.collateral_deposited += amount;
+
+    emit!(CollateralToppedUp {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        amount,
+        collateral_remaining: participant.remaining_collateral(),
+        top_up_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Liquidate<'info> {
+    pub liquidator: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.mint_address == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        mut,
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
+        bump,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+    )]
+    pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    // The under-secured member, not the caller
+    #[account(
+        mut,
+        seeds = [b"participant", chit_fund.key().as_ref(), participant.owner.as_ref()],
+        bump,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+        constraint = participant.has_borrowed @ ChitFundError::LiquidationBeforeBorrowing,
+        constraint = !participant.is_liquidated @ ChitFundError::AlreadyLiquidated,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    #[account(
+        mut,
+        constraint = liquidator_collateral_account.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+    )]
+    pub liquidator_collateral_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Must be the feed stored at init; read through the oracle adapters
+    pub price_feed: Option<UncheckedAccount<'info>>,
+
+    pub collateral_token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn liquidate_participant(ctx: Context<Liquidate>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let participant = &mut ctx.accounts.participant;
+
+    let collateral_price = load_collateral_price(
+        chit_fund,
+        ctx.accounts.price_feed.as_deref(),
+        ctx.accounts.mint.decimals,
+        ctx.accounts.collateral_mint.decimals,
+    )?;
+
+    require!(
+        participant.is_below_maintenance(chit_fund, &collateral_price),
+        ChitFundError::CollateralAboveMaintenance
+    );
+    let obligation = participant.obligation(chit_fund);
+    let remaining = participant.remaining_collateral();
+    let collateral_value = collateral_price.value_of(remaining);
+
+    // Set aside enough to cover the remaining cycles and pay the liquidator on top.
+    // If that is more than the member has, both shrink in proportion.
+    let target = collateral_price.amount_for(obligation);
+    let target_bounty = (target as u128 * LIQUIDATION_BOUNTY_BPS as u128 / BPS_DENOMINATOR as u128) as u64;
+    let (reserved, bounty) = if remaining >= target + target_bounty {
+        (target, target_bounty)
+    } else {
+        let bounty = (remaining as u128 * LIQUIDATION_BOUNTY_BPS as u128
+            / (BPS_DENOMINATOR + LIQUIDATION_BOUNTY_BPS) as u128) as u64;
+        (remaining - bounty, bounty)
+    };
+
+    // Pay the bounty
+    let transfer_fee = if bounty > 0 {
+        VaultSigner::collateral(
+            &ctx.accounts.collateral_vault,
+            &ctx.accounts.collateral_mint,
+            &ctx.accounts.collateral_token_program,
+            chit_fund.key(),
+            ctx.bumps.collateral_vault,
+        ).transfer(ctx.accounts.liquidator_collateral_account.to_account_info(), bounty)?
+    } else {
+        0
+    };
+
+    participant.is_liquidated = true;
+    participant.liquidation_reserve = reserved;
+    participant.collateral_slashed += bounty;
+    chit_fund.total_collateral_amount -= bounty;
+
+    emit!(ParticipantLiquidated {
+        chit_fund: chit_fund.key(),
+        participant: participant.key(),
+        liquidator: ctx.accounts.liquidator.key(),
+        obligation,
+        collateral_value,
+        reserved,
+        bounty,
+        transfer_fee,
+        liquidation_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct CollateralToppedUp {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub collateral_remaining: u64,
+    pub top_up_time: i64,
+}
+
+#[event]
+pub struct ParticipantLiquidated {
+    pub chit_fund: Pubkey,
+    pub participant: Pubkey,
+    pub liquidator: Pubkey,
+    pub obligation: u64,
+    pub collateral_value: u64,
+    pub reserved: u64,
+    pub bounty: u64,
+    pub transfer_fee: u64,
+    pub liquidation_time: i64,
+}
@@ -7,4 +7,28 @@ pub mod contributions;
 pub use disburse::*;
 pub mod disburse;
 pub use withdraw::*;
-pub mod withdraw;
\ No newline at end of file
+pub mod withdraw;
+pub use auction::*;
+pub mod auction;
+pub use dividend::*;
+pub mod dividend;
+pub use draw::*;
+pub mod draw;
+pub use vote::*;
+pub mod vote;
+pub use defaults::*;
+pub mod defaults;
+pub use emergency::*;
+pub mod emergency;
+pub use cancel::*;
+pub mod cancel;
+pub use dissolve::*;
+pub mod dissolve;
+pub use close::*;
+pub mod close;
+pub use oracle::*;
+pub mod oracle;
+pub use margin::*;
+pub mod margin;
+pub use config::*;
+pub mod config;
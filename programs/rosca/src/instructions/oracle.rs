@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+
+use crate::state::TestOracle;
+use crate::error::*;
+
+// Lets a local validator price collateral without a live oracle network. The instruction
+// stays in the interface but only does anything in builds with the test-oracle feature.
+#[derive(Accounts)]
+pub struct SetTestOracle<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TestOracle::INIT_SPACE,
+        seeds = [b"test_oracle", authority.key().as_ref()],
+        bump,
+    )]
+    pub oracle: Box<Account<'info, TestOracle>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(not(feature = "test-oracle"))]
+pub fn set_test_oracle_price(_ctx: Context<SetTestOracle>, _price: i64, _expo: i32) -> Result<()> {
+    err!(ChitFundError::TestOracleDisabled)
+}
+
+#[cfg(feature = "test-oracle")]
+pub fn set_test_oracle_price(ctx: Context<SetTestOracle>, price: i64, expo: i32) -> Result<()> {
+    require!(price > 0, ChitFundError::InvalidPrice);
+
+    let oracle = &mut ctx.accounts.oracle;
+    let current_time = Clock::get()?.unix_timestamp;
+
+    oracle.authority = ctx.accounts.authority.key();
+    oracle.price = price;
+    oracle.expo = expo;
+    oracle.publish_time = current_time;
+
+    emit!(TestOraclePriceSet {
+        oracle: oracle.key(),
+        price,
+        expo,
+        publish_time: current_time,
+    });
+
+    Ok(())
+}
+
+#[cfg(feature = "test-oracle")]
+#[event]
+pub struct TestOraclePriceSet {
+    pub oracle: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ChitFund, Participant, PayoutVote, SelectionMode};
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct VotePayout<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode == SelectionMode::Vote @ ChitFundError::NotVoteFund,
+        constraint = !chit_fund.has_selected_borrower() @ ChitFundError::WinnerAlreadyDrawn,
+        constraint = Clock::get()?.unix_timestamp < chit_fund.cycle_end_time() @ ChitFundError::VotingClosed,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+
+    #[account(
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
+        bump,
+        constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
+    )]
+    pub participant: Box<Account<'info, Participant>>,
+
+    // One vote per member per cycle
+    #[account(
+        init,
+        payer = user,
+        space = 8 + PayoutVote::INIT_SPACE,
+        seeds = [b"payout_vote", chit_fund.key().as_ref(), &[chit_fund.current_cycle], user.key().as_ref()],
+        bump,
+    )]
+    pub vote: Box<Account<'info, PayoutVote>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// The pot goes to the first member backed by more than the quorum of the group before the cycle ends
+pub fn vote_payout_recipient(ctx: Context<VotePayout>, candidate: Pubkey) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+    let vote = &mut ctx.accounts.vote;
+
+    let index = chit_fund
+        .participant_index(candidate)
+        .ok_or(ChitFundError::InvalidVoteCandidate)?;
+    require!(
+        !chit_fund.borrowed_participants[index],
+        ChitFundError::InvalidVoteCandidate
+    );
+
+    vote.chit_fund = chit_fund.key();
+    vote.cycle = chit_fund.current_cycle;
+    vote.voter = ctx.accounts.user.key();
+    vote.candidate = candidate;
+    chit_fund.payout_votes[index] += 1;
+
+    if chit_fund.has_priority_borrower() {
+        // An approved emergency request takes this cycle's pot
+        chit_fund.selected_borrower = chit_fund.priority_borrower;
+    } else if chit_fund.payout_vote_passed(index) {
+        chit_fund.selected_borrower = candidate;
+    }
+
+    emit!(PayoutVoteCast {
+        chit_fund: chit_fund.key(),
+        cycle: chit_fund.current_cycle,
+        voter: vote.voter,
+        candidate,
+        votes: chit_fund.payout_votes[index],
+        selected_borrower: chit_fund.selected_borrower,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResolvePayoutVote<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.is_active() @ ChitFundError::ChitFundInactive,
+        constraint = chit_fund.selection_mode == SelectionMode::Vote @ ChitFundError::NotVoteFund,
+        constraint = !chit_fund.has_selected_borrower() @ ChitFundError::WinnerAlreadyDrawn,
+        constraint = Clock::get()?.unix_timestamp >= chit_fund.cycle_end_time() @ ChitFundError::VotingOpen,
+    )]
+    pub chit_fund: Box<Account<'info, ChitFund>>,
+}
+
+// Voting closes with the cycle. If nobody reached the quorum by then, anyone can settle the
+// vote: the member with the most votes wins, and ties or a silent vote go by join order.
+pub fn resolve_payout_vote(ctx: Context<ResolvePayoutVote>) -> Result<()> {
+    let chit_fund = &mut ctx.accounts.chit_fund;
+
+    chit_fund.selected_borrower = if chit_fund.has_priority_borrower() {
+        chit_fund.priority_borrower
+    } else {
+        chit_fund.vote_leader().ok_or(ChitFundError::NoEligibleBorrowers)?
+    };
+
+    let votes = chit_fund
+        .participant_index(chit_fund.selected_borrower)
+        .map_or(0, |index| chit_fund.payout_votes[index]);
+
+    emit!(PayoutVoteResolved {
+        chit_fund: chit_fund.key(),
+        cycle: chit_fund.current_cycle,
+        selected_borrower: chit_fund.selected_borrower,
+        votes,
+        resolve_time: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[event]
+pub struct PayoutVoteCast {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub voter: Pubkey,
+    pub candidate: Pubkey,
+    pub votes: u8,
+    pub selected_borrower: Pubkey,
+}
+
+#[event]
+pub struct PayoutVoteResolved {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub selected_borrower: Pubkey,
+    pub votes: u8,
+    pub resolve_time: i64,
+}
@@ -1,49 +1,59 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token_interface::{self,Mint, TokenAccount, TokenInterface, TransferChecked};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
-use crate::state::{ChitFund, Participant};
+use crate::state::{ChitFund, FundStatus, Participant};
 use crate::error::*;
+use crate::native::{is_native_mint, Unwrap};
+use crate::vault::VaultSigner;
 #[derive(Accounts)]
 pub struct WithdrawCollateral<'info> {
     #[account(mut)]
     pub user: Signer<'info>, 
 
-    pub mint: InterfaceAccount<'info, Mint>,
+    pub collateral_mint: InterfaceAccount<'info, Mint>,
  
     #[account(
         mut,
-        seeds = [mint.key().as_ref()],
-        bump,
-        constraint = !chit_fund.is_active @ ChitFundError::ChitFundActive, // Can only withdraw after chit fund ends
+        seeds = [b"chit_fund", chit_fund.creator.as_ref(), &chit_fund.fund_id.to_le_bytes()],
+        bump = chit_fund.bump,
+        constraint = chit_fund.collateral_mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
+        constraint = chit_fund.status == FundStatus::Completed @ ChitFundError::ChitFundActive, // Can only withdraw after chit fund ends
     )]
     pub chit_fund: Box<Account<'info, ChitFund>>,
     #[account(
         mut,
-        seeds = [b"collateral_vault", mint.key().as_ref()],
+        seeds = [b"collateral_vault", chit_fund.key().as_ref()],
         bump,
-        constraint = collateral_vault.mint == mint.key() @ ChitFundError::InvalidContributionMint,
+        constraint = collateral_vault.mint == collateral_mint.key() @ ChitFundError::InvalidCollateralMint,
     )]
     pub collateral_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut, 
-        seeds = [user.key().as_ref()],
+        seeds = [b"participant", chit_fund.key().as_ref(), user.key().as_ref()],
         bump,
         constraint = participant.owner == user.key() @ ChitFundError::Unauthorized,
+        constraint = participant.chit_fund == chit_fund.key() @ ChitFundError::ParticipantFundMismatch,
         constraint = participant.has_borrowed @ ChitFundError::WithdrawBeforeBorrowing, // Can only withdraw after borrowing
+        constraint = participant.remaining_collateral() > 0 @ ChitFundError::CollateralAlreadyWithdrawn,
     )]
     pub participant: Box<Account<'info, Participant>>,
 
+    // Left out on native SOL collateral to withdraw straight to the wallet
     #[account(
         mut,
-        associated_token::mint = mint,
+        associated_token::mint = collateral_mint,
         associated_token::authority = user,
-        associated_token::token_program = token_program,
+        associated_token::token_program = collateral_token_program,
     )]
-    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub user_collateral_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Created, filled and closed within this instruction to unwrap SOL
+    #[account(mut, seeds = [b"unwrap", chit_fund.key().as_ref()], bump)]
+    pub unwrap_account: Option<UncheckedAccount<'info>>,
 
-    pub token_program: Interface<'info, TokenInterface>,
+    pub collateral_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
@@ -52,46 +62,55 @@ pub fn withdraw_collateral(ctx: Context<WithdrawCollateral>) -> Result<()> {
     let chit_fund = &mut ctx.accounts.chit_fund;
     let participant = &mut ctx.accounts.participant;
 
-    // Transfer funds
-    let transfer_cpi_accounts = TransferChecked {
-        from: ctx.accounts.collateral_vault.to_account_info(),
-        to: ctx.accounts.user_token_account.to_account_info(),
-        authority: ctx.accounts.collateral_vault.to_account_info(),
-        mint: ctx.accounts.mint.to_account_info(),
+    // Collateral used to cover defaults is not refunded
+    let refund_amount = participant.remaining_collateral();
+
+    let chit_fund_key = chit_fund.key();
+
+    // Without a token account the collateral is unwrapped to the wallet as SOL
+    let unwrap = match &ctx.accounts.user_collateral_account {
+        Some(_) => None,
+        None => {
+            require!(is_native_mint(&ctx.accounts.collateral_mint.key()), ChitFundError::TokenAccountRequired);
+            let unwrap_account = ctx.accounts.unwrap_account.as_ref().ok_or(ChitFundError::UnwrapAccountRequired)?;
+            Some(Unwrap::open(
+                unwrap_account.to_account_info(),
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.collateral_mint.to_account_info(),
+                ctx.accounts.collateral_token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                chit_fund_key,
+                ctx.bumps.unwrap_account.unwrap_or_default(),
+            )?)
+        }
+    };
+    let destination = match &unwrap {
+        Some(unwrap) => unwrap.account.clone(),
+        None => ctx.accounts.user_collateral_account.as_ref().ok_or(ChitFundError::TokenAccountRequired)?.to_account_info(),
     };
 
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-
-    let mint_key = ctx.accounts.mint.key();
-
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        b"collateral_vault",
-        mint_key.as_ref(),
-        &[ctx.bumps.collateral_vault]
-    ]];
-
-    let cpi_ctx = CpiContext::new(
-        cpi_program,
-        transfer_cpi_accounts,
-    ).with_signer(signer_seeds);
-
-    let decimals = ctx.accounts.mint.decimals;
-
-    token_interface::transfer_checked(cpi_ctx, chit_fund.collateral_requirement, decimals)?;
-
-    // Update token amounts based on mint verification
-    match ctx.accounts.mint.key() {
-        key if key == participant.usdc_address => {
-            chit_fund.total_contribution_amount -= chit_fund.collateral_requirement;
-            participant.total_contributed -= chit_fund.collateral_requirement;
-        }
-        _ => return Err(ChitFundError::InvalidContributionMint.into())
+    // Transfer funds; a transfer-fee mint withholds its fee from the member
+    let transfer_fee = VaultSigner::collateral(
+        &ctx.accounts.collateral_vault,
+        &ctx.accounts.collateral_mint,
+        &ctx.accounts.collateral_token_program,
+        chit_fund_key,
+        ctx.bumps.collateral_vault,
+    ).transfer(destination, refund_amount)?;
+
+    if let Some(unwrap) = unwrap {
+        unwrap.close(ctx.accounts.user.to_account_info(), refund_amount)?;
     }
 
+    // Update token amounts
+    chit_fund.total_collateral_amount -= refund_amount;
+    participant.collateral_returned += refund_amount;
+
     emit!(CollateralWithdrawn {
         chit_fund: chit_fund.key(),
         participant: participant.key(),
-        amount: chit_fund.collateral_requirement,
+        amount: refund_amount,
+        transfer_fee,
         withdraw_time: Clock::get()?.unix_timestamp,
     });
 
@@ -103,5 +122,6 @@ pub struct CollateralWithdrawn {
     pub chit_fund: Pubkey,
     pub participant: Pubkey,
     pub amount: u64,
+    pub transfer_fee: u64,
     pub withdraw_time: i64,
 }
@@ -1,11 +1,17 @@
 use anchor_lang::prelude::*;
 use instructions::*;
 use constants::*;
+use state::SelectionMode;
 
 mod state;
 mod instructions;
 mod constants;
 mod error;
+mod oracle;
+mod extensions;
+mod native;
+mod vault;
+mod payout;
 
 declare_id!("6AQ26gghMkL77dWnoGhxX5iW1sU13zKVA4yy1fs8C4qr");
 
@@ -13,8 +19,89 @@ declare_id!("6AQ26gghMkL77dWnoGhxX5iW1sU13zKVA4yy1fs8C4qr");
 pub mod rosca {
     use super::*;
 
-    pub fn init_chit_fund(ctx: Context<InitializeChitFund>, contribution_amount: u64, cycle_duration: i64, total_cycles: u8, collateral_requirement: u64, max_participants: u8, disbursement_schedule: [u64; MAX_CYCLES]) -> Result<()> {
-        initialize_chit_fund(ctx, contribution_amount, cycle_duration, total_cycles, collateral_requirement, max_participants, disbursement_schedule)
+    pub fn init_protocol_config(ctx: Context<InitializeProtocolConfig>, treasury: Pubkey, protocol_fee_bps: u16, max_participants: u8, max_cycles: u8, min_cycle_duration: i64, max_foreman_commission_bps: u16) -> Result<()> {
+        initialize_protocol_config(ctx, treasury, protocol_fee_bps, max_participants, max_cycles, min_cycle_duration, max_foreman_commission_bps)
+    }
+
+    pub fn update_config(ctx: Context<UpdateProtocolConfig>, protocol_fee_bps: u16, max_participants: u8, max_cycles: u8, min_cycle_duration: i64, max_foreman_commission_bps: u16) -> Result<()> {
+        update_protocol_config(ctx, protocol_fee_bps, max_participants, max_cycles, min_cycle_duration, max_foreman_commission_bps)
+    }
+
+    pub fn set_admin(ctx: Context<UpdateProtocolConfig>, new_admin: Pubkey) -> Result<()> {
+        set_protocol_admin(ctx, new_admin)
+    }
+
+    pub fn set_treasury(ctx: Context<UpdateProtocolConfig>, treasury: Pubkey) -> Result<()> {
+        set_protocol_treasury(ctx, treasury)
+    }
+
+    pub fn set_mint_policy(ctx: Context<UpdateProtocolConfig>, allow_freeze_authority: bool, allow_permanent_delegate: bool, allow_transfer_hook: bool, allow_transfer_fee: bool) -> Result<()> {
+        set_protocol_mint_policy(ctx, allow_freeze_authority, allow_permanent_delegate, allow_transfer_hook, allow_transfer_fee)
+    }
+
+    pub fn allow_mint(ctx: Context<UpdateMintAllowlist>) -> Result<()> {
+        add_allowed_mint(ctx)
+    }
+
+    pub fn disallow_mint(ctx: Context<UpdateMintAllowlist>) -> Result<()> {
+        remove_allowed_mint(ctx)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn init_chit_fund(ctx: Context<InitializeChitFund>, fund_id: u64, contribution_amount: u64, cycle_duration: i64, total_cycles: u8, collateral_requirement: u64, max_participants: u8, disbursement_schedule: [u64; MAX_CYCLES], max_discount_bps: u16, contribution_deadline_offset: i64, grace_period: i64, late_fee_bps: u16, min_participants: u8, enrollment_deadline: i64, payout_holdback: bool, foreman_commission_bps: u16, selection_mode: SelectionMode, payout_order: Vec<u8>) -> Result<()> {
+        initialize_chit_fund(ctx, fund_id, contribution_amount, cycle_duration, total_cycles, collateral_requirement, max_participants, disbursement_schedule, max_discount_bps, contribution_deadline_offset, grace_period, late_fee_bps, min_participants, enrollment_deadline, payout_holdback, foreman_commission_bps, selection_mode, payout_order)
+    }
+
+    pub fn start_fund(ctx: Context<StartFund>, payout_order: Vec<u8>) -> Result<()> {
+        start_chit_fund(ctx, payout_order)
+    }
+
+    pub fn cancel_fund(ctx: Context<CancelFund>) -> Result<()> {
+        cancel_chit_fund(ctx)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+        claim_participant_refund(ctx)
+    }
+
+    pub fn vote_payout(ctx: Context<VotePayout>, candidate: Pubkey) -> Result<()> {
+        vote_payout_recipient(ctx, candidate)
+    }
+
+    pub fn resolve_vote(ctx: Context<ResolvePayoutVote>) -> Result<()> {
+        resolve_payout_vote(ctx)
+    }
+
+    pub fn vote_dissolve(ctx: Context<VoteDissolve>) -> Result<()> {
+        vote_dissolve_fund(ctx)
+    }
+
+    pub fn dissolve_fund<'info>(ctx: Context<'_, '_, 'info, 'info, DissolveFund<'info>>) -> Result<()> {
+        dissolve_chit_fund(ctx)
+    }
+
+    pub fn close_participant(ctx: Context<CloseParticipant>) -> Result<()> {
+        close_participant_account(ctx)
+    }
+
+    pub fn close_fund(ctx: Context<CloseFund>) -> Result<()> {
+        close_chit_fund(ctx)
+    }
+
+    pub fn close_auction_account(ctx: Context<CloseAuctionAccount>) -> Result<()> {
+        close_cycle_auction_account(ctx)
+    }
+
+    pub fn close_payout_vote(ctx: Context<ClosePayoutVote>) -> Result<()> {
+        close_payout_vote_account(ctx)
+    }
+
+    pub fn close_dissolve_vote(ctx: Context<CloseDissolveVote>) -> Result<()> {
+        close_dissolve_vote_account(ctx)
+    }
+
+    pub fn close_emergency_vote(ctx: Context<CloseEmergencyVote>) -> Result<()> {
+        close_emergency_vote_account(ctx)
     }
 
     pub fn join_chit(ctx: Context<JoinChitFund>) -> Result<()> {
@@ -25,13 +112,65 @@ pub mod rosca {
         make_contribution(ctx)
     }
 
-    pub fn disburse(ctx: Context<DisburseFunds>) -> Result<()> {
+    pub fn claim_payout(ctx: Context<DisburseFunds>) -> Result<()> {
         disburse_funds(ctx)
-    }    
+    }
 
     pub fn withdraw(ctx: Context<WithdrawCollateral>) -> Result<()> {
         withdraw_collateral(ctx)
     }
+
+    pub fn mark_default(ctx: Context<MarkDefault>) -> Result<()> {
+        mark_participant_default(ctx)
+    }
+
+    pub fn commit_seed(ctx: Context<CommitSeed>, commitment: [u8; 32]) -> Result<()> {
+        commit_draw_seed(ctx, commitment)
+    }
+
+    pub fn reveal_seed(ctx: Context<RevealSeed>, seed: [u8; 32]) -> Result<()> {
+        reveal_draw_seed(ctx, seed)
+    }
+
+    pub fn draw_winner(ctx: Context<DrawWinner>) -> Result<()> {
+        draw_cycle_winner(ctx)
+    }
+
+    pub fn request_emergency(ctx: Context<RequestEmergency>, reason_code: u8) -> Result<()> {
+        request_emergency_payout(ctx, reason_code)
+    }
+
+    pub fn vote_emergency(ctx: Context<VoteEmergency>, approve: bool) -> Result<()> {
+        vote_emergency_payout(ctx, approve)
+    }
+
+    pub fn place_bid(ctx: Context<PlaceBid>, discount: u64) -> Result<()> {
+        place_auction_bid(ctx, discount)
+    }
+
+    pub fn close_auction(ctx: Context<CloseAuction>) -> Result<()> {
+        close_cycle_auction(ctx)
+    }
+
+    pub fn settle_auction(ctx: Context<SettleAuction>) -> Result<()> {
+        settle_cycle_auction(ctx)
+    }
+
+    pub fn claim_dividend(ctx: Context<ClaimDividend>) -> Result<()> {
+        claim_member_dividend(ctx)
+    }
+
+    pub fn top_up_collateral(ctx: Context<TopUpCollateral>, amount: u64) -> Result<()> {
+        top_up_participant_collateral(ctx, amount)
+    }
+
+    pub fn liquidate(ctx: Context<Liquidate>) -> Result<()> {
+        liquidate_participant(ctx)
+    }
+
+    pub fn set_test_oracle(ctx: Context<SetTestOracle>, price: i64, expo: i32) -> Result<()> {
+        set_test_oracle_price(ctx, price, expo)
+    }
 }
 
 
@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program::{self, Allocate, Assign, CreateAccount, Transfer};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_2022::spl_token_2022;
+use anchor_spl::token_interface::{self, CloseAccount, InitializeAccount3, SyncNative};
+
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID || *mint == spl_token_2022::native_mint::ID
+}
+
+// Moves lamports from a member's wallet into a wSOL vault
+pub fn wrap_lamports<'info>(
+    from: AccountInfo<'info>,
+    vault: AccountInfo<'info>,
+    amount: u64,
+    system_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+) -> Result<()> {
+    let cpi_ctx = CpiContext::new(
+        system_program,
+        Transfer {
+            from,
+            to: vault.clone(),
+        },
+    );
+    system_program::transfer(cpi_ctx, amount)?;
+
+    let cpi_ctx = CpiContext::new(token_program, SyncNative { account: vault });
+    token_interface::sync_native(cpi_ctx)
+}
+
+// The temporary wSOL account at [b"unwrap", chit_fund] that payments to a member without a
+// token account pass through. It is its own authority so the program can close it in the same
+// instruction. The payer funds its rent and gets it back on close, so only the unwrapped
+// amount reaches the member's wallet.
+pub struct Unwrap<'info> {
+    pub account: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    chit_fund: Pubkey,
+    bump: u8,
+}
+
+impl<'info> Unwrap<'info> {
+    // Creates the account for `native_mint` under that mint's token program
+    pub fn open(
+        account: AccountInfo<'info>,
+        payer: AccountInfo<'info>,
+        native_mint: AccountInfo<'info>,
+        token_program: AccountInfo<'info>,
+        system_program: AccountInfo<'info>,
+        chit_fund: Pubkey,
+        bump: u8,
+    ) -> Result<Self> {
+        let unwrap = Self {
+            account,
+            payer,
+            token_program,
+            system_program,
+            chit_fund,
+            bump,
+        };
+        let bump = [unwrap.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"unwrap", unwrap.chit_fund.as_ref(), &bump]];
+
+        let space = spl_token::state::Account::LEN;
+        let lamports = Rent::get()?.minimum_balance(space);
+        let current_lamports = unwrap.account.lamports();
+
+        if current_lamports == 0 {
+            let cpi_ctx = CpiContext::new(
+                unwrap.system_program.clone(),
+                CreateAccount {
+                    from: unwrap.payer.clone(),
+                    to: unwrap.account.clone(),
+                },
+            ).with_signer(signer_seeds);
+            system_program::create_account(cpi_ctx, lamports, space as u64, unwrap.token_program.key)?;
+        } else {
+            // Anyone can send lamports to the address, which would make create_account fail,
+            // so top it up to rent exemption and allocate it in place instead
+            let top_up = lamports.saturating_sub(current_lamports);
+            if top_up > 0 {
+                let cpi_ctx = CpiContext::new(
+                    unwrap.system_program.clone(),
+                    Transfer {
+                        from: unwrap.payer.clone(),
+                        to: unwrap.account.clone(),
+                    },
+                );
+                system_program::transfer(cpi_ctx, top_up)?;
+            }
+
+            let cpi_ctx = CpiContext::new(
+                unwrap.system_program.clone(),
+                Allocate {
+                    account_to_allocate: unwrap.account.clone(),
+                },
+            ).with_signer(signer_seeds);
+            system_program::allocate(cpi_ctx, space as u64)?;
+
+            let cpi_ctx = CpiContext::new(
+                unwrap.system_program.clone(),
+                Assign {
+                    account_to_assign: unwrap.account.clone(),
+                },
+            ).with_signer(signer_seeds);
+            system_program::assign(cpi_ctx, unwrap.token_program.key)?;
+        }
+
+        let cpi_ctx = CpiContext::new(
+            unwrap.token_program.clone(),
+            InitializeAccount3 {
+                account: unwrap.account.clone(),
+                mint: native_mint,
+                authority: unwrap.account.clone(),
+            },
+        );
+        token_interface::initialize_account3(cpi_ctx)?;
+
+        Ok(unwrap)
+    }
+
+    // Closes the account back to the payer, rent and any stray lamports included, and
+    // forwards the unwrapped amount to the member's wallet
+    pub fn close(self, wallet: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let bump = [self.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"unwrap", self.chit_fund.as_ref(), &bump]];
+
+        let cpi_ctx = CpiContext::new(
+            self.token_program,
+            CloseAccount {
+                account: self.account.clone(),
+                destination: self.payer.clone(),
+                authority: self.account,
+            },
+        ).with_signer(signer_seeds);
+        token_interface::close_account(cpi_ctx)?;
+
+        if amount == 0 || self.payer.key == wallet.key {
+            return Ok(());
+        }
+
+        let cpi_ctx = CpiContext::new(
+            self.system_program,
+            Transfer {
+                from: self.payer,
+                to: wallet,
+            },
+        );
+        system_program::transfer(cpi_ctx, amount)
+    }
+}
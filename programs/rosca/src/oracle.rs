@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::*;
+use crate::error::*;
+use crate::state::ChitFund;
+#[cfg(feature = "test-oracle")]
+use crate::state::TestOracle;
+
+// A price read from a feed: one whole collateral token is worth price * 10^expo
+// whole contribution tokens
+#[derive(Clone, Copy, Debug)]
+pub struct OraclePrice {
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+// Anything that can quote the collateral mint in contribution-mint terms
+pub trait PriceSource {
+    fn owner() -> Pubkey;
+    fn read_price(account: &AccountInfo) -> Result<OraclePrice>;
+}
+
+// Pyth-style price account: the aggregate price of a legacy Pyth price account
+pub struct PythPriceFeed;
+
+impl PythPriceFeed {
+    const MAGIC: u32 = 0xa1b2c3d4;
+    const VERSION: u32 = 2;
+    const ACCOUNT_TYPE_PRICE: u32 = 3;
+    const STATUS_TRADING: u32 = 1;
+
+    const MAGIC_OFFSET: usize = 0;
+    const VERSION_OFFSET: usize = 4;
+    const ACCOUNT_TYPE_OFFSET: usize = 8;
+    const EXPO_OFFSET: usize = 20;
+    const TIMESTAMP_OFFSET: usize = 96;
+    const AGG_PRICE_OFFSET: usize = 208;
+    const AGG_CONF_OFFSET: usize = 216;
+    const AGG_STATUS_OFFSET: usize = 224;
+    const MIN_LEN: usize = 240;
+}
+
+impl PriceSource for PythPriceFeed {
+    fn owner() -> Pubkey {
+        PYTH_ORACLE_PROGRAM_ID
+    }
+
+    fn read_price(account: &AccountInfo) -> Result<OraclePrice> {
+        let data = account.try_borrow_data()?;
+        require!(data.len() >= Self::MIN_LEN, ChitFundError::InvalidPriceFeed);
+
+        let read = |offset: usize, len: usize| -> &[u8] { &data[offset..offset + len] };
+
+        let magic = u32::from_le_bytes(read(Self::MAGIC_OFFSET, 4).try_into().unwrap());
+        let version = u32::from_le_bytes(read(Self::VERSION_OFFSET, 4).try_into().unwrap());
+        let account_type = u32::from_le_bytes(read(Self::ACCOUNT_TYPE_OFFSET, 4).try_into().unwrap());
+        let status = u32::from_le_bytes(read(Self::AGG_STATUS_OFFSET, 4).try_into().unwrap());
+        require!(
+            magic == Self::MAGIC && version == Self::VERSION && account_type == Self::ACCOUNT_TYPE_PRICE,
+            ChitFundError::InvalidPriceFeed
+        );
+        require!(status == Self::STATUS_TRADING, ChitFundError::InvalidPrice);
+
+        let price = i64::from_le_bytes(read(Self::AGG_PRICE_OFFSET, 8).try_into().unwrap());
+        let conf = u64::from_le_bytes(read(Self::AGG_CONF_OFFSET, 8).try_into().unwrap());
+        require!(price > 0, ChitFundError::InvalidPrice);
+
+        // A wide band means the publishers disagree and the price cannot be relied on
+        require!(
+            conf as u128 * BPS_DENOMINATOR as u128 <= price as u128 * MAX_PRICE_CONFIDENCE_BPS as u128,
+            ChitFundError::PriceConfidenceTooWide
+        );
+
+        Ok(OraclePrice {
+            price,
+            expo: i32::from_le_bytes(read(Self::EXPO_OFFSET, 4).try_into().unwrap()),
+            publish_time: i64::from_le_bytes(read(Self::TIMESTAMP_OFFSET, 8).try_into().unwrap()),
+        })
+    }
+}
+
+// Program-owned price account set through set_test_oracle, for local validators.
+// Anyone can set these, so they are never accepted outside test-oracle builds.
+#[cfg(feature = "test-oracle")]
+pub struct TestOracleFeed;
+
+#[cfg(feature = "test-oracle")]
+impl PriceSource for TestOracleFeed {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+
+    fn read_price(account: &AccountInfo) -> Result<OraclePrice> {
+        let data = account.try_borrow_data()?;
+        let oracle = TestOracle::try_deserialize(&mut &data[..])?;
+
+        Ok(OraclePrice {
+            price: oracle.price,
+            expo: oracle.expo,
+            publish_time: oracle.publish_time,
+        })
+    }
+}
+
+// Picks the adapter from the account owner
+pub fn read_price_feed(account: &AccountInfo) -> Result<OraclePrice> {
+    if account.owner == &PythPriceFeed::owner() {
+        return PythPriceFeed::read_price(account);
+    }
+
+    #[cfg(feature = "test-oracle")]
+    if account.owner == &TestOracleFeed::owner() {
+        return TestOracleFeed::read_price(account);
+    }
+
+    err!(ChitFundError::InvalidPriceFeed)
+}
+
+// Converts between collateral and contribution base units at a feed price
+#[derive(Clone, Copy, Debug)]
+pub struct CollateralPrice {
+    pub price: u64,
+    // Power of ten turning collateral base units times price into contribution base units
+    pub scale: i32,
+}
+
+impl CollateralPrice {
+    pub fn new(oracle_price: OraclePrice, mint_decimals: u8, collateral_decimals: u8) -> Result<Self> {
+        require!(oracle_price.price > 0, ChitFundError::InvalidPrice);
+
+        Ok(Self {
+            price: oracle_price.price as u64,
+            scale: oracle_price.expo + mint_decimals as i32 - collateral_decimals as i32,
+        })
+    }
+
+    // Collateral and contributions in the same mint are worth the same
+    pub fn parity() -> Self {
+        Self { price: 1, scale: 0 }
+    }
+
+    // Contribution-mint value of `amount` collateral, rounded down
+    pub fn value_of(&self, amount: u64) -> u64 {
+        let gross = amount as u128 * self.price as u128;
+        let value = if self.scale >= 0 {
+            gross.saturating_mul(10u128.saturating_pow(self.scale as u32))
+        } else {
+            gross / 10u128.saturating_pow(self.scale.unsigned_abs())
+        };
+        value.min(u64::MAX as u128) as u64
+    }
+
+    // Collateral needed to be worth `value`, rounded up
+    pub fn amount_for(&self, value: u64) -> u64 {
+        let (numerator, denominator) = if self.scale >= 0 {
+            (
+                value as u128,
+                (self.price as u128).saturating_mul(10u128.saturating_pow(self.scale as u32)),
+            )
+        } else {
+            (
+                (value as u128).saturating_mul(10u128.saturating_pow(self.scale.unsigned_abs())),
+                self.price as u128,
+            )
+        };
+        numerator.div_ceil(denominator).min(u64::MAX as u128) as u64
+    }
+}
+
+// Current price of the fund's collateral, checked against the feed chosen at init
+pub fn load_collateral_price(
+    chit_fund: &ChitFund,
+    price_feed: Option<&AccountInfo>,
+    mint_decimals: u8,
+    collateral_decimals: u8,
+) -> Result<CollateralPrice> {
+    if chit_fund.collateral_mint == chit_fund.mint_address {
+        return Ok(CollateralPrice::parity());
+    }
+
+    let price_feed = price_feed.ok_or(ChitFundError::PriceFeedRequired)?;
+    require!(
+        price_feed.key() == chit_fund.price_feed,
+        ChitFundError::InvalidPriceFeed
+    );
+
+    let oracle_price = read_price_feed(price_feed)?;
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        current_time - oracle_price.publish_time <= MAX_PRICE_AGE,
+        ChitFundError::StalePrice
+    );
+
+    CollateralPrice::new(oracle_price, mint_decimals, collateral_decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pyth_account_data(account_type: u32, price: i64, conf: u64) -> Vec<u8> {
+        let mut data = vec![0u8; PythPriceFeed::MIN_LEN];
+        data[0..4].copy_from_slice(&PythPriceFeed::MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&PythPriceFeed::VERSION.to_le_bytes());
+        data[8..12].copy_from_slice(&account_type.to_le_bytes());
+        data[20..24].copy_from_slice(&(-8i32).to_le_bytes());
+        data[96..104].copy_from_slice(&1_000i64.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[216..224].copy_from_slice(&conf.to_le_bytes());
+        data[224..228].copy_from_slice(&PythPriceFeed::STATUS_TRADING.to_le_bytes());
+        data
+    }
+
+    fn read_pyth(mut data: Vec<u8>) -> Result<OraclePrice> {
+        let key = Pubkey::new_unique();
+        let owner = PYTH_ORACLE_PROGRAM_ID;
+        let mut lamports = 0;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        read_price_feed(&account)
+    }
+
+    #[test]
+    fn pyth_price_account_is_read() {
+        let price = read_pyth(pyth_account_data(3, 150_000_000, 100_000)).unwrap();
+        assert_eq!(price.price, 150_000_000);
+        assert_eq!(price.expo, -8);
+        assert_eq!(price.publish_time, 1_000);
+    }
+
+    #[test]
+    fn pyth_rejects_other_account_types() {
+        assert!(read_pyth(pyth_account_data(2, 150_000_000, 100_000)).is_err());
+    }
+
+    #[test]
+    fn pyth_rejects_wide_confidence() {
+        // 3% band against a 2% limit
+        assert!(read_pyth(pyth_account_data(3, 100_000_000, 3_000_000)).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "test-oracle"))]
+    fn program_owned_accounts_are_not_price_feeds() {
+        let key = Pubkey::new_unique();
+        let owner = crate::ID;
+        let mut lamports = 0;
+        let mut data = vec![0u8; 64];
+        let account = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        assert!(read_price_feed(&account).is_err());
+    }
+
+    // SOL (9 decimals) at $150 against USDC (6 decimals), as Pyth reports it
+    fn sol_in_usdc() -> CollateralPrice {
+        let oracle_price = OraclePrice { price: 15_000_000_000, expo: -8, publish_time: 0 };
+        CollateralPrice::new(oracle_price, 6, 9).unwrap()
+    }
+
+    #[test]
+    fn collateral_is_valued_in_contribution_units() {
+        let price = sol_in_usdc();
+        assert_eq!(price.scale, -11);
+        assert_eq!(price.value_of(1_000_000_000), 150_000_000);
+        assert_eq!(price.amount_for(150_000_000), 1_000_000_000);
+    }
+
+    #[test]
+    fn collateral_amounts_round_in_the_fund_s_favour() {
+        let price = sol_in_usdc();
+        // Seven lamports are needed to cover one base unit, and are valued back at one
+        assert_eq!(price.amount_for(1), 7);
+        assert_eq!(price.value_of(7), 1);
+        assert_eq!(price.value_of(6), 0);
+    }
+
+    #[test]
+    fn positive_scale_multiplies_through() {
+        // A whole-unit collateral token worth 2 of a 6-decimal contribution mint
+        let oracle_price = OraclePrice { price: 2, expo: 0, publish_time: 0 };
+        let price = CollateralPrice::new(oracle_price, 6, 0).unwrap();
+        assert_eq!(price.value_of(3), 6_000_000);
+        assert_eq!(price.amount_for(6_000_001), 4);
+    }
+
+    #[test]
+    fn parity_leaves_amounts_unchanged() {
+        let price = CollateralPrice::parity();
+        assert_eq!(price.value_of(123), 123);
+        assert_eq!(price.amount_for(123), 123);
+    }
+}
@@ -0,0 +1,180 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{ChitFund, Participant};
+use crate::error::*;
+use crate::native::Unwrap;
+use crate::vault::VaultSigner;
+
+// Everything a cycle's pot is paid out through, whether the winner was drawn, voted,
+// scheduled or won the auction
+pub struct PotAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub contribution_vault: VaultSigner<'info>,
+    pub collateral_vault: VaultSigner<'info>,
+    pub recipient_token_account: Option<AccountInfo<'info>>,
+    pub recipient_collateral_account: Option<AccountInfo<'info>>,
+    pub recipient_wallet: Option<AccountInfo<'info>>,
+    pub unwrap_account: Option<AccountInfo<'info>>,
+    pub unwrap_bump: u8,
+    pub creator_fee_account: Option<AccountInfo<'info>>,
+    pub treasury_fee_account: Option<AccountInfo<'info>>,
+    pub system_program: AccountInfo<'info>,
+}
+
+pub struct PotPayout {
+    pub held_back: u64,
+    pub collateral_amount: u64,
+    pub transfer_fee: u64,
+    pub collateral_transfer_fee: u64,
+}
+
+// Pays `payout_amount` to the cycle's winner and records it. Contributions covered by seized
+// collateral are paid in kind, and the foreman's commission, the protocol fee and any holdback
+// come out of the token part. Advancing the cycle is left to the caller.
+pub fn pay_out_pot<'info>(
+    accounts: PotAccounts<'info>,
+    chit_fund: &mut ChitFund,
+    participant: &mut Participant,
+    payout_amount: u64,
+) -> Result<PotPayout> {
+    let chit_fund_key = accounts.contribution_vault.chit_fund;
+
+    // Contributions covered by seized collateral are paid out in kind
+    let token_amount = payout_amount.saturating_sub(chit_fund.pot_shortfall);
+
+    // The foreman's commission and the protocol fee come off the top of the pot
+    let commission = chit_fund.foreman_commission(payout_amount).min(token_amount);
+    let protocol_fee = chit_fund.protocol_fee(payout_amount).min(token_amount - commission);
+    let token_amount = token_amount - commission - protocol_fee;
+
+    // With holdback on, what the winner still owes stays in the vault as escrow
+    let held_back = chit_fund.payout_holdback(token_amount);
+    let token_amount = token_amount - held_back;
+    let collateral_amount = chit_fund.seized_collateral;
+
+    // Members who joined without a token account are paid in SOL through a temporary wSOL account
+    let unwrap_tokens = token_amount > 0 && participant.usdc_address == participant.owner;
+    let unwrap_collateral = collateral_amount > 0 && participant.collateral_address == participant.owner;
+    let unwrap = if unwrap_tokens || unwrap_collateral {
+        let unwrap_account = accounts.unwrap_account.clone().ok_or(ChitFundError::UnwrapAccountRequired)?;
+        let unwrap_vault = if unwrap_tokens {
+            &accounts.contribution_vault
+        } else {
+            &accounts.collateral_vault
+        };
+        Some(Unwrap::open(
+            unwrap_account,
+            accounts.payer.clone(),
+            unwrap_vault.mint.clone(),
+            unwrap_vault.token_program.clone(),
+            accounts.system_program.clone(),
+            chit_fund_key,
+            accounts.unwrap_bump,
+        )?)
+    } else {
+        None
+    };
+    let unwrap_destination = unwrap.as_ref().map(|unwrap| unwrap.account.clone());
+    let token_destination = if unwrap_tokens {
+        unwrap_destination.clone()
+    } else {
+        accounts.recipient_token_account.clone()
+    };
+    let collateral_destination = if unwrap_collateral {
+        unwrap_destination
+    } else {
+        accounts.recipient_collateral_account.clone()
+    };
+
+    // The winner, the foreman and the treasury each bear the transfer fee on what they receive
+    let transfer_fee = if token_amount > 0 {
+        let destination = token_destination.ok_or(ChitFundError::InvalidBorrowerAccount)?;
+        accounts.contribution_vault.transfer(destination, token_amount)?
+    } else {
+        0
+    };
+
+    // Pay the foreman
+    if commission > 0 {
+        let creator_fee_account = accounts.creator_fee_account.clone().ok_or(ChitFundError::InvalidCreatorFeeAccount)?;
+        let transfer_fee = accounts.contribution_vault.transfer(creator_fee_account, commission)?;
+
+        emit!(ForemanCommissionPaid {
+            chit_fund: chit_fund_key,
+            creator: chit_fund.creator,
+            cycle: chit_fund.current_cycle,
+            amount: commission,
+            payout_amount,
+            transfer_fee,
+        });
+    }
+
+    // Pay the protocol
+    if protocol_fee > 0 {
+        let treasury_fee_account = accounts.treasury_fee_account.clone().ok_or(ChitFundError::InvalidTreasuryAccount)?;
+        let transfer_fee = accounts.contribution_vault.transfer(treasury_fee_account, protocol_fee)?;
+
+        emit!(ProtocolFeePaid {
+            chit_fund: chit_fund_key,
+            treasury: chit_fund.protocol_treasury,
+            cycle: chit_fund.current_cycle,
+            amount: protocol_fee,
+            transfer_fee,
+        });
+    }
+
+    // Transfer seized collateral
+    let collateral_transfer_fee = if collateral_amount > 0 {
+        let destination = collateral_destination.ok_or(ChitFundError::InvalidBorrowerAccount)?;
+        accounts.collateral_vault.transfer(destination, collateral_amount)?
+    } else {
+        0
+    };
+
+    // Hand the unwrapped SOL to the winner
+    if let Some(unwrap) = unwrap {
+        let recipient_wallet = accounts.recipient_wallet.ok_or(ChitFundError::UnwrapAccountRequired)?;
+        let unwrapped = if unwrap_tokens { token_amount } else { 0 }
+            + if unwrap_collateral { collateral_amount } else { 0 };
+        unwrap.close(recipient_wallet, unwrapped)?;
+    }
+
+    // Update borrowed status
+    chit_fund.mark_borrowed(participant.owner);
+    participant.has_borrowed = true;
+    participant.borrowed_cycle = Some(chit_fund.current_cycle);
+    participant.payout_received += payout_amount - commission - protocol_fee - held_back;
+    participant.payout_escrow += held_back;
+
+    // Update token amounts
+    chit_fund.total_contribution_amount -= token_amount + commission + protocol_fee;
+    chit_fund.foreman_commissions_paid += commission;
+    chit_fund.protocol_fees_paid += protocol_fee;
+    chit_fund.total_collateral_amount -= collateral_amount;
+
+    Ok(PotPayout {
+        held_back,
+        collateral_amount,
+        transfer_fee,
+        collateral_transfer_fee,
+    })
+}
+
+#[event]
+pub struct ForemanCommissionPaid {
+    pub chit_fund: Pubkey,
+    pub creator: Pubkey,
+    pub cycle: u8,
+    pub amount: u64,
+    pub payout_amount: u64,
+    pub transfer_fee: u64,
+}
+
+#[event]
+pub struct ProtocolFeePaid {
+    pub chit_fund: Pubkey,
+    pub treasury: Pubkey,
+    pub cycle: u8,
+    pub amount: u64,
+    pub transfer_fee: u64,
+}
@@ -1,33 +1,92 @@
 use anchor_lang::prelude::*;
 use crate::constants::*;
+use crate::error::*;
+use crate::oracle::CollateralPrice;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum FundStatus {
+    Enrolling,
+    Active,
+    Completed,
+    Cancelled,
+    Dissolved,
+}
+
+// How each cycle's pot finds its recipient
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum SelectionMode {
+    Lottery,
+    Auction,
+    FixedOrder,
+    Vote,
+}
 
 #[account]
 #[derive(InitSpace)]
 pub struct ChitFund {
     // Admin/Config data
     pub creator: Pubkey,
+    pub fund_id: u64,
+    pub bump: u8,
     pub mint_address: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub price_feed: Pubkey,           // Prices collateral_mint in mint_address; unset when they match
     pub contribution_amount: u64,
     pub cycle_duration: i64,
     pub total_cycles: u8,        // Using u8 since MAX_CYCLES is 12
-    pub collateral_requirement: u64,
+    pub collateral_requirement: u64, // Value in mint_address units, posted in collateral_mint at the feed price
     pub max_participants: u8,
+    pub min_participants: u8,
+    pub enrollment_deadline: i64,
+    pub selection_mode: SelectionMode,
+    pub max_discount_bps: u16,   // Cap on auction bids
+    pub contribution_deadline_offset: i64,   // Seconds into the cycle before a payment is late
+    pub grace_period: i64,                   // Seconds after the deadline before a default can be marked
+    pub late_fee_bps: u16,
+    pub payout_holdback: bool,               // Hold back what a winner still owes from their pot
+    pub foreman_commission_bps: u16,         // Creator's cut of each pot for running the group
+    pub protocol_fee_bps: u16,               // Protocol's cut of each pot, fixed at init
+    pub protocol_treasury: Pubkey,           // Owner of the token accounts protocol fees go to
 
     // State tracking
     pub current_cycle: u8,
-    pub is_active: bool,
+    pub status: FundStatus,
     pub last_disbursement_time: i64,
+
+    // Lottery draw for the current cycle
+    pub draw_seed: [u8; 32],
+    pub commit_count: u8,
+    pub reveal_count: u8,
+    pub unrevealed: [bool; MAX_PARTICIPANTS],   // Committed this cycle without revealing yet
+    pub selected_borrower: Pubkey,     // Pubkey::default() until drawn
+    pub priority_borrower: Pubkey,     // Approved emergency request, wins the next draw or auction
+    pub emergency_queue: [bool; MAX_PARTICIPANTS],   // Approved while another was pending, promoted in join order
+    pub payout_order: [u8; MAX_CYCLES],    // FixedOrder: index into participants paid each cycle
+    pub has_payout_order: bool,
+    pub payout_votes: [u8; MAX_PARTICIPANTS],   // Vote: this cycle's votes for each member
     
     // Participant tracking
     pub participants: [Pubkey; MAX_PARTICIPANTS],
     pub participants_count: u8,
     pub borrowed_participants: [bool; MAX_PARTICIPANTS],
+    pub paid_participants: [bool; MAX_PARTICIPANTS],   // Contributions received for the current cycle
+    pub paid_count: u8,
     
     // Financial tracking
     pub disbursement_schedule: [u64; MAX_CYCLES],
     pub contribution_vault: Pubkey,    
     pub collateral_vault: Pubkey,      
     pub total_contribution_amount: u64, 
+    pub total_collateral_amount: u64,
+    pub seized_collateral: u64,       // Collateral taken from defaulters this cycle, paid to the borrower in kind
+    pub pot_shortfall: u64,           // Contributions covered by seized collateral instead of tokens this cycle
+    pub dividend_per_member: u64,      // Cumulative auction discounts and late fees credited to each member
+    pub late_fees_collected: u64,
+    pub foreman_commissions_paid: u64,
+    pub protocol_fees_paid: u64,
+    pub default_count: u16,           // Defaults marked across all members and cycles, up to MAX_PARTICIPANTS * MAX_CYCLES
+    pub participants_closed: u8,
+    pub dissolve_votes: u8,
 }
 
 #[account]
@@ -37,11 +96,21 @@ pub struct Participant {
     pub owner: Pubkey,
     pub chit_fund: Pubkey,
     pub usdc_address: Pubkey,
+    pub collateral_address: Pubkey,
     
     // State tracking
     pub has_borrowed: bool,
     pub is_emergency_requested: bool,
+    pub emergency_reason: u8,
+    pub emergency_votes_for: u8,
+    pub emergency_votes_against: u8,
+    pub emergency_approved: bool,
+    pub emergency_requests: u8,        // Requests made so far; keys each request's votes
     pub contributions: [bool; MAX_CYCLES],
+    pub defaults: [bool; MAX_CYCLES],  // Cycles covered from collateral instead of paid
+    pub seed_commitment: [u8; 32],
+    pub commit_cycle: Option<u8>,
+    pub has_revealed: bool,
     
     // Time tracking
     pub join_time: i64,               
@@ -50,4 +119,755 @@ pub struct Participant {
     // Financial tracking
     pub total_contributed: u64,        
     pub borrowed_cycle: Option<u8>,    
-}
\ No newline at end of file
+    pub dividends_claimed: u64,
+    pub dividend_excluded: u64,        // Share of dividend_per_member this member is not entitled to
+    pub default_count: u8,
+
+    // Collateral ledger
+    pub collateral_deposited: u64,
+    pub collateral_slashed: u64,
+    pub collateral_returned: u64,
+    pub is_refunded: bool,            // Refund or settlement of a cancelled or dissolved fund paid
+    pub payout_received: u64,
+    pub settlement_amount: u64,       // Owed from the contribution vault after dissolution
+    pub settlement_collateral: u64,   // Owed from seized collateral after dissolution
+    pub is_liquidated: bool,
+    pub liquidation_reserve: u64,     // Collateral set aside by liquidation to cover the remaining cycles
+    pub payout_escrow: u64,           // Payout held back until the remaining contributions come in
+    pub default_shortfall: u64,       // Defaulted contributions that collateral could not cover
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct CycleAuction {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub payer: Pubkey,                 // Paid the rent, which goes back to them on close
+
+    // Bidding state
+    pub best_bidder: Pubkey,
+    pub best_discount: u64,
+    pub bid_count: u32,                // Members may bid again, so this is not bounded by the roster
+
+    // Outcome
+    pub is_closed: bool,
+    pub is_settled: bool,
+    pub winner: Pubkey,
+    pub payout_amount: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct EmergencyVote {
+    pub chit_fund: Pubkey,
+    pub requester: Pubkey,
+    pub voter: Pubkey,
+    pub approve: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DissolveVote {
+    pub chit_fund: Pubkey,
+    pub voter: Pubkey,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PayoutVote {
+    pub chit_fund: Pubkey,
+    pub cycle: u8,
+    pub voter: Pubkey,
+    pub candidate: Pubkey,
+}
+
+// Program-wide settings, one per deployment
+#[account]
+#[derive(InitSpace)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+    pub protocol_fee_bps: u16,
+    pub treasury: Pubkey,                    // Owner of the token accounts protocol fees go to
+    #[max_len(MAX_ALLOWED_MINTS)]
+    pub allowed_mints: Vec<Pubkey>,          // Mints funds may contribute or post collateral in
+
+    // Risky mint features funds may still use
+    pub allow_freeze_authority: bool,
+    pub allow_permanent_delegate: bool,
+    pub allow_transfer_hook: bool,
+    pub allow_transfer_fee: bool,
+
+    // Bounds checked when a fund is created
+    pub max_participants: u8,
+    pub max_cycles: u8,
+    pub min_cycle_duration: i64,
+    pub max_foreman_commission_bps: u16,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct TestOracle {
+    pub authority: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+impl Participant {
+    pub fn remaining_collateral(&self) -> u64 {
+        self.collateral_deposited - self.collateral_slashed - self.collateral_returned - self.liquidation_reserve
+    }
+
+    // Cycles from the current one onwards not yet paid
+    pub fn unpaid_cycles(&self, chit_fund: &ChitFund) -> u64 {
+        (chit_fund.current_cycle..chit_fund.total_cycles)
+            .filter(|&i| !self.contributions[i as usize])
+            .count() as u64
+    }
+
+    // Contributions still owed from the current cycle on
+    pub fn obligation(&self, chit_fund: &ChitFund) -> u64 {
+        self.unpaid_cycles(chit_fund) * chit_fund.contribution_amount
+    }
+
+    // Liquidation answers a fall in the collateral's price: the remaining collateral must be
+    // worth less than the margin on what was required at join, or on what is still owed if
+    // that is less. Collateral in the contribution mint cannot lose value against it.
+    pub fn is_below_maintenance(&self, chit_fund: &ChitFund, price: &CollateralPrice) -> bool {
+        if chit_fund.collateral_mint == chit_fund.mint_address {
+            return false;
+        }
+        let secured = chit_fund.collateral_requirement.min(self.obligation(chit_fund));
+        let collateral_value = price.value_of(self.remaining_collateral());
+        secured > 0
+            && (collateral_value as u128) * (BPS_DENOMINATOR as u128)
+                < (secured as u128) * (MAINTENANCE_MARGIN_BPS as u128)
+    }
+
+    // Releases the current cycle's share of the liquidation reserve
+    pub fn release_reserve_share(&mut self, chit_fund: &ChitFund) -> u64 {
+        let unpaid_cycles = self.unpaid_cycles(chit_fund);
+        if unpaid_cycles == 0 {
+            return 0;
+        }
+        let share = self.liquidation_reserve / unpaid_cycles;
+        self.liquidation_reserve -= share;
+        share
+    }
+
+    // Everything put into the pot, including cycles covered from collateral
+    pub fn pot_contributions(&self, chit_fund: &ChitFund) -> u64 {
+        let contributed_cycles = self.contributions.iter().filter(|&&paid| paid).count() as u64;
+        contributed_cycles * chit_fund.contribution_amount
+    }
+
+    // Contributions paid in tokens, excluding cycles covered from collateral
+    pub fn paid_contributions(&self, chit_fund: &ChitFund) -> u64 {
+        let paid_cycles = (0..MAX_CYCLES)
+            .filter(|&i| self.contributions[i] && !self.defaults[i])
+            .count() as u64;
+        paid_cycles * chit_fund.contribution_amount
+    }
+
+    // An emergency request passes once more than the quorum of the other members approve
+    pub fn emergency_vote_passed(&self, chit_fund: &ChitFund) -> bool {
+        let eligible_voters = (chit_fund.participants_count as u64).saturating_sub(1);
+        self.emergency_votes_for as u64 * BPS_DENOMINATOR > eligible_voters * EMERGENCY_QUORUM_BPS
+    }
+
+    // ...and fails once enough of them vote against that the quorum can no longer be reached
+    pub fn emergency_vote_failed(&self, chit_fund: &ChitFund) -> bool {
+        let eligible_voters = (chit_fund.participants_count as u64).saturating_sub(1);
+        let possible_for = eligible_voters.saturating_sub(self.emergency_votes_against as u64);
+        possible_for * BPS_DENOMINATOR <= eligible_voters * EMERGENCY_QUORUM_BPS
+    }
+
+    // What a member is owed (positive) or owes (negative) if the fund is dissolved now: everything
+    // put into the pot plus unclaimed dividends, less payouts received and any defaulted
+    // contributions their collateral could not cover
+    pub fn net_position(&self, chit_fund: &ChitFund) -> i128 {
+        self.pot_contributions(chit_fund) as i128
+            + self.claimable_dividend(chit_fund) as i128
+            - self.default_shortfall as i128
+            - self.payout_received as i128
+    }
+
+    pub fn claimable_dividend(&self, chit_fund: &ChitFund) -> u64 {
+        chit_fund.dividend_per_member - self.dividend_excluded - self.dividends_claimed
+    }
+
+    // Nothing left to pay to or collect from this member
+    pub fn is_settled(&self, chit_fund: &ChitFund) -> bool {
+        let refund_paid = chit_fund.status == FundStatus::Completed || self.is_refunded;
+        // Dissolving settles a shortfall against the member's collateral, otherwise it is a debt
+        // still owed to the pot and the account stays as its record
+        let shortfall_settled = chit_fund.status == FundStatus::Dissolved || self.default_shortfall == 0;
+        chit_fund.has_ended()
+            && refund_paid
+            && shortfall_settled
+            && self.payout_escrow == 0
+            && self.remaining_collateral() == 0
+            && self.claimable_dividend(chit_fund) == 0
+    }
+}
+
+impl ProtocolConfig {
+    pub fn is_mint_allowed(&self, mint: &Pubkey) -> bool {
+        self.allowed_mints.contains(mint)
+    }
+
+    // Runtime bounds can only tighten the compile-time limits the account layouts are sized for
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.protocol_fee_bps <= MAX_PROTOCOL_FEE_BPS,
+            ChitFundError::InvalidProtocolFee
+        );
+        require!(
+            self.max_participants > 0 && self.max_participants as usize <= MAX_PARTICIPANTS,
+            ChitFundError::InvalidProtocolBounds
+        );
+        require!(
+            self.max_cycles > 0 && self.max_cycles as usize <= MAX_CYCLES,
+            ChitFundError::InvalidProtocolBounds
+        );
+        require!(
+            self.min_cycle_duration >= MIN_CYCLE_DURATION,
+            ChitFundError::InvalidProtocolBounds
+        );
+        require!(
+            self.max_foreman_commission_bps <= MAX_FOREMAN_COMMISSION_BPS,
+            ChitFundError::InvalidForemanCommission
+        );
+        Ok(())
+    }
+}
+
+impl ChitFund {
+    pub fn is_active(&self) -> bool {
+        self.status == FundStatus::Active
+    }
+
+    pub fn has_ended(&self) -> bool {
+        matches!(self.status, FundStatus::Completed | FundStatus::Cancelled | FundStatus::Dissolved)
+    }
+
+    // Member this cycle's pot goes to, Pubkey::default() until one is chosen
+    pub fn payout_recipient(&self) -> Pubkey {
+        match self.selection_mode {
+            SelectionMode::FixedOrder => {
+                let index = self.payout_order[self.current_cycle as usize] as usize;
+                self.participants[index]
+            }
+            _ => self.selected_borrower,
+        }
+    }
+
+    // Checks that `order` pays each of the first `total_cycles` members exactly once
+    pub fn parse_payout_order(order: &[u8], total_cycles: u8) -> Result<[u8; MAX_CYCLES]> {
+        require!(order.len() == total_cycles as usize, ChitFundError::InvalidPayoutOrder);
+
+        let mut seen = [false; MAX_CYCLES];
+        let mut payout_order = [0; MAX_CYCLES];
+        for (cycle, &index) in order.iter().enumerate() {
+            require!(
+                (index as usize) < order.len() && !seen[index as usize],
+                ChitFundError::InvalidPayoutOrder
+            );
+            seen[index as usize] = true;
+            payout_order[cycle] = index;
+        }
+        Ok(payout_order)
+    }
+
+    // Checks that the pots pay out everything one member per cycle contributes, that each can
+    // be paid from what has been collected by then, and that nothing is scheduled past the end
+    pub fn validate_disbursement_schedule(
+        schedule: &[u64; MAX_CYCLES],
+        total_cycles: u8,
+        contribution_amount: u64,
+    ) -> Result<()> {
+        let collected_per_cycle = contribution_amount as u128 * total_cycles as u128;
+        let scheduled: u128 = schedule.iter().map(|&pot| pot as u128).sum();
+        require!(
+            scheduled == collected_per_cycle * total_cycles as u128,
+            ChitFundError::InvalidDisbursementTotal
+        );
+
+        let mut paid_out: u128 = 0;
+        for (cycle, &pot) in schedule.iter().enumerate() {
+            let in_fund = cycle < total_cycles as usize;
+            paid_out += pot as u128;
+            require!(
+                (pot > 0) == in_fund && paid_out <= collected_per_cycle * (cycle as u128 + 1),
+                ChitFundError::InvalidDisbursementSchedule
+            );
+        }
+        Ok(())
+    }
+
+    // Swaps a member into the current cycle's slot so they are paid next
+    pub fn move_up_payout_order(&mut self, owner: Pubkey) {
+        let Some(index) = self.participant_index(owner) else {
+            return;
+        };
+        let current = self.current_cycle as usize;
+        let total = self.total_cycles as usize;
+        if let Some(slot) = (current..total).find(|&cycle| self.payout_order[cycle] as usize == index) {
+            self.payout_order.swap(current, slot);
+        }
+    }
+
+    pub fn payout_vote_passed(&self, index: usize) -> bool {
+        self.payout_votes[index] as u64 * BPS_DENOMINATOR > self.participants_count as u64 * PAYOUT_VOTE_QUORUM_BPS
+    }
+
+    // The member still owed a pot with the most votes, ties and a silent vote going to
+    // whoever joined first
+    pub fn vote_leader(&self) -> Option<Pubkey> {
+        (0..self.participants_count as usize)
+            .filter(|&i| !self.borrowed_participants[i])
+            .fold(None, |leader: Option<usize>, i| match leader {
+                Some(best) if self.payout_votes[best] >= self.payout_votes[i] => Some(best),
+                _ => Some(i),
+            })
+            .map(|i| self.participants[i])
+    }
+
+    pub fn cycle_end_time(&self) -> i64 {
+        self.last_disbursement_time + self.cycle_duration
+    }
+
+    pub fn contribution_deadline(&self) -> i64 {
+        self.last_disbursement_time + self.contribution_deadline_offset
+    }
+
+    // Members who have not paid by now can be defaulted
+    pub fn grace_end_time(&self) -> i64 {
+        self.contribution_deadline() + self.grace_period
+    }
+
+    // Part of a payout made in the current cycle kept back against the winner's later contributions
+    pub fn payout_holdback(&self, payout: u64) -> u64 {
+        if !self.payout_holdback {
+            return 0;
+        }
+        let cycles_left = (self.total_cycles - self.current_cycle - 1) as u64;
+        payout.min(cycles_left * self.contribution_amount)
+    }
+
+    pub fn foreman_commission(&self, payout: u64) -> u64 {
+        (payout as u128 * self.foreman_commission_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    pub fn protocol_fee(&self, payout: u64) -> u64 {
+        (payout as u128 * self.protocol_fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    // Credits an amount as a dividend to every member but the one it came from, who must
+    // add the returned share to their dividend_excluded. The remainder stays in the vault.
+    pub fn credit_dividend(&mut self, amount: u64) -> u64 {
+        let recipients = (self.participants_count as u64).saturating_sub(1);
+        if recipients == 0 {
+            return 0;
+        }
+        let per_member = amount / recipients;
+        self.dividend_per_member += per_member;
+        per_member
+    }
+
+    // Rounding a settled fund may leave in a vault: every dividend credit and the dissolve
+    // split can strand up to one base unit per member
+    pub fn dust_allowance(&self) -> u64 {
+        let members = self.participants_count as u64;
+        let splits = self.total_cycles as u64 * (members + 1) + 1;
+        splits * members
+    }
+
+    pub fn late_fee(&self) -> u64 {
+        (self.contribution_amount as u128 * self.late_fee_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    pub fn reveal_end_time(&self) -> i64 {
+        self.cycle_end_time() + REVEAL_WINDOW
+    }
+
+    pub fn has_selected_borrower(&self) -> bool {
+        self.selected_borrower != Pubkey::default()
+    }
+
+    // Largest discount a bidder may offer on this cycle's pot
+    pub fn max_discount(&self) -> u64 {
+        let pot = self.disbursement_schedule[self.current_cycle as usize];
+        (pot as u128 * self.max_discount_bps as u128 / BPS_DENOMINATOR as u128) as u64
+    }
+
+    pub fn participant_index(&self, owner: Pubkey) -> Option<usize> {
+        (0..self.participants_count as usize).find(|&i| self.participants[i] == owner)
+    }
+
+    // Members still owed a pot, less those who committed a seed and then withheld it.
+    // If every remaining member withheld, they stay in so the cycle can still be drawn.
+    pub fn draw_candidates(&self) -> Vec<Pubkey> {
+        let unpaid: Vec<usize> = (0..self.participants_count as usize)
+            .filter(|&i| !self.borrowed_participants[i])
+            .collect();
+        let revealed: Vec<usize> = unpaid.iter().copied().filter(|&i| !self.unrevealed[i]).collect();
+        let candidates = if revealed.is_empty() { unpaid } else { revealed };
+        candidates.into_iter().map(|i| self.participants[i]).collect()
+    }
+
+    pub fn has_priority_borrower(&self) -> bool {
+        self.priority_borrower != Pubkey::default()
+    }
+
+    // Gives an approved emergency request the next pot, or queues it behind one already
+    // waiting. Returns whether it was queued.
+    pub fn approve_emergency(&mut self, owner: Pubkey) -> bool {
+        if self.has_priority_borrower() {
+            if let Some(i) = self.participant_index(owner) {
+                self.emergency_queue[i] = true;
+            }
+            return true;
+        }
+        self.priority_borrower = owner;
+
+        // A fixed order has no draw to win, so the requester takes the current slot instead
+        if self.selection_mode == SelectionMode::FixedOrder {
+            self.move_up_payout_order(owner);
+        }
+        false
+    }
+
+    pub fn mark_borrowed(&mut self, owner: Pubkey) {
+        if let Some(i) = self.participant_index(owner) {
+            self.borrowed_participants[i] = true;
+            self.emergency_queue[i] = false;
+        }
+        if self.priority_borrower == owner {
+            self.priority_borrower = Pubkey::default();
+        }
+    }
+
+    pub fn mark_paid(&mut self, owner: Pubkey) {
+        if let Some(i) = self.participant_index(owner) {
+            if !self.paid_participants[i] {
+                self.paid_participants[i] = true;
+                self.paid_count += 1;
+            }
+        }
+    }
+
+    pub fn all_contributions_in(&self) -> bool {
+        self.paid_count == self.participants_count
+    }
+
+    pub fn dissolve_vote_passed(&self) -> bool {
+        self.dissolve_votes as u64 * BPS_DENOMINATOR > self.participants_count as u64 * DISSOLVE_QUORUM_BPS
+    }
+
+    // A full round's worth of defaults means the group can no longer carry itself
+    pub fn too_many_defaults(&self) -> bool {
+        self.default_count >= self.participants_count as u16
+    }
+
+    // Moves the fund on to the next cycle once a pot has been paid out
+    pub fn advance_cycle(&mut self, now: i64) {
+        self.current_cycle += 1;
+        self.last_disbursement_time = now;
+
+        // Reset the lottery draw for the new cycle
+        self.draw_seed = [0; 32];
+        self.commit_count = 0;
+        self.reveal_count = 0;
+        self.unrevealed = [false; MAX_PARTICIPANTS];
+        self.selected_borrower = Pubkey::default();
+        self.payout_votes = [0; MAX_PARTICIPANTS];
+
+        // Nobody has paid into the new cycle yet
+        self.paid_participants = [false; MAX_PARTICIPANTS];
+        self.paid_count = 0;
+        self.seized_collateral = 0;
+        self.pot_shortfall = 0;
+
+        // A queued emergency request takes its turn once the one ahead of it has been paid
+        if !self.has_priority_borrower() {
+            let next = (0..self.participants_count as usize).find(|&i| self.emergency_queue[i]);
+            if let Some(i) = next {
+                self.emergency_queue[i] = false;
+                self.approve_emergency(self.participants[i]);
+            }
+        }
+
+        if self.current_cycle == self.total_cycles {
+            self.status = FundStatus::Completed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Zeroed accounts, as `init` leaves them before the handler fills them in
+    pub(crate) fn empty_fund() -> ChitFund {
+        ChitFund::deserialize(&mut &vec![0u8; ChitFund::INIT_SPACE][..]).unwrap()
+    }
+
+    pub(crate) fn empty_participant() -> Participant {
+        Participant::deserialize(&mut &vec![0u8; Participant::INIT_SPACE][..]).unwrap()
+    }
+
+    // The repo's test setup: 200 of collateral against 100 a cycle over 4 cycles
+    fn fund_after_first_payout(collateral_mint: Pubkey) -> (ChitFund, Participant) {
+        let mut chit_fund = empty_fund();
+        chit_fund.mint_address = Pubkey::new_unique();
+        chit_fund.collateral_mint = collateral_mint;
+        chit_fund.contribution_amount = 100;
+        chit_fund.collateral_requirement = 200;
+        chit_fund.total_cycles = 4;
+        chit_fund.current_cycle = 1;
+
+        let mut participant = empty_participant();
+        participant.collateral_deposited = 200;
+        participant.contributions[0] = true;
+        participant.has_borrowed = true;
+        (chit_fund, participant)
+    }
+
+    #[test]
+    fn same_mint_collateral_is_never_liquidated() {
+        let (mut chit_fund, participant) = fund_after_first_payout(Pubkey::default());
+        chit_fund.collateral_mint = chit_fund.mint_address;
+        assert!(!participant.is_below_maintenance(&chit_fund, &CollateralPrice::parity()));
+    }
+
+    #[test]
+    fn winner_is_not_liquidated_without_a_price_move() {
+        let (chit_fund, participant) = fund_after_first_payout(Pubkey::new_unique());
+        assert_eq!(participant.obligation(&chit_fund), 300);
+        assert!(!participant.is_below_maintenance(&chit_fund, &CollateralPrice { price: 1, scale: 0 }));
+    }
+
+    #[test]
+    fn price_fall_past_the_margin_allows_liquidation() {
+        let (chit_fund, participant) = fund_after_first_payout(Pubkey::new_unique());
+        // Down 10%: still above the 80% margin
+        assert!(!participant.is_below_maintenance(&chit_fund, &CollateralPrice { price: 9, scale: -1 }));
+        // Down 30%: below it
+        assert!(participant.is_below_maintenance(&chit_fund, &CollateralPrice { price: 7, scale: -1 }));
+    }
+
+    #[test]
+    fn nothing_owed_means_nothing_to_liquidate() {
+        let (mut chit_fund, participant) = fund_after_first_payout(Pubkey::new_unique());
+        chit_fund.current_cycle = 4;
+        assert!(!participant.is_below_maintenance(&chit_fund, &CollateralPrice { price: 1, scale: -3 }));
+    }
+
+    #[test]
+    fn members_who_withhold_a_seed_are_left_out_of_the_draw() {
+        let mut chit_fund = empty_fund();
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        chit_fund.participants[..3].copy_from_slice(&members);
+        chit_fund.participants_count = 3;
+        chit_fund.borrowed_participants[0] = true;
+        chit_fund.unrevealed[1] = true;
+        assert_eq!(chit_fund.draw_candidates(), vec![members[2]]);
+
+        // Everyone left withheld, so nobody is left out
+        chit_fund.unrevealed[2] = true;
+        assert_eq!(chit_fund.draw_candidates(), vec![members[1], members[2]]);
+    }
+
+    #[test]
+    fn vote_leader_breaks_ties_by_join_order() {
+        let mut chit_fund = empty_fund();
+        let members: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        chit_fund.participants[..4].copy_from_slice(&members);
+        chit_fund.participants_count = 4;
+        chit_fund.borrowed_participants[0] = true;
+        assert_eq!(chit_fund.vote_leader(), Some(members[1]));
+
+        chit_fund.payout_votes[2] = 1;
+        chit_fund.payout_votes[3] = 1;
+        assert_eq!(chit_fund.vote_leader(), Some(members[2]));
+
+        chit_fund.payout_votes[3] = 2;
+        assert_eq!(chit_fund.vote_leader(), Some(members[3]));
+    }
+
+    #[test]
+    fn late_fee_is_shared_with_everyone_but_the_payer() {
+        let mut chit_fund = empty_fund();
+        chit_fund.participants_count = 4;
+        let mut late = empty_participant();
+        let on_time = empty_participant();
+
+        late.dividend_excluded += chit_fund.credit_dividend(10);
+        assert_eq!(chit_fund.dividend_per_member, 3);
+        assert_eq!(late.claimable_dividend(&chit_fund), 0);
+        assert_eq!(on_time.claimable_dividend(&chit_fund), 3);
+    }
+
+    #[test]
+    fn emergency_request_fails_once_the_quorum_is_out_of_reach() {
+        let mut chit_fund = empty_fund();
+        chit_fund.participants_count = 5;
+        let mut requester = empty_participant();
+
+        requester.emergency_votes_for = 2;
+        requester.emergency_votes_against = 1;
+        assert!(!requester.emergency_vote_passed(&chit_fund));
+        assert!(!requester.emergency_vote_failed(&chit_fund));
+
+        requester.emergency_votes_against = 2;
+        assert!(requester.emergency_vote_failed(&chit_fund));
+
+        requester.emergency_votes_for = 3;
+        requester.emergency_votes_against = 1;
+        assert!(requester.emergency_vote_passed(&chit_fund));
+    }
+
+    #[test]
+    fn net_position_nets_payouts_and_uncovered_defaults() {
+        let mut chit_fund = empty_fund();
+        chit_fund.contribution_amount = 100;
+        chit_fund.dividend_per_member = 15;
+
+        // Paid two cycles, one more covered from collateral
+        let mut saver = empty_participant();
+        saver.contributions[..3].copy_from_slice(&[true, true, true]);
+        saver.defaults[2] = true;
+        assert_eq!(saver.net_position(&chit_fund), 315);
+
+        // Took a 380 pot after two cycles, and collateral fell 40 short on a third
+        let mut borrower = empty_participant();
+        borrower.contributions[..3].copy_from_slice(&[true, true, true]);
+        borrower.defaults[2] = true;
+        borrower.default_shortfall = 40;
+        borrower.payout_received = 380;
+        borrower.dividend_excluded = 15;
+        assert_eq!(borrower.net_position(&chit_fund), -120);
+    }
+
+    #[test]
+    fn disbursement_schedule_is_paid_from_what_has_been_collected() {
+        let mut schedule = [0; MAX_CYCLES];
+        schedule[..3].copy_from_slice(&[300, 300, 300]);
+        assert!(ChitFund::validate_disbursement_schedule(&schedule, 3, 100).is_ok());
+
+        // Rising pots are fine as long as each is covered by then
+        schedule[..3].copy_from_slice(&[200, 300, 400]);
+        assert!(ChitFund::validate_disbursement_schedule(&schedule, 3, 100).is_ok());
+
+        // More up front than the first cycle collects
+        schedule[..3].copy_from_slice(&[400, 300, 200]);
+        assert_eq!(
+            ChitFund::validate_disbursement_schedule(&schedule, 3, 100).unwrap_err(),
+            ChitFundError::InvalidDisbursementSchedule.into()
+        );
+
+        // More or less in total than the fund collects
+        schedule[..3].copy_from_slice(&[300, 300, 301]);
+        assert_eq!(
+            ChitFund::validate_disbursement_schedule(&schedule, 3, 100).unwrap_err(),
+            ChitFundError::InvalidDisbursementTotal.into()
+        );
+        schedule[..3].copy_from_slice(&[300, 300, 299]);
+        assert_eq!(
+            ChitFund::validate_disbursement_schedule(&schedule, 3, 100).unwrap_err(),
+            ChitFundError::InvalidDisbursementTotal.into()
+        );
+
+        // A cycle without a pot, and a pot past the last cycle
+        schedule[..4].copy_from_slice(&[300, 0, 600, 0]);
+        assert_eq!(
+            ChitFund::validate_disbursement_schedule(&schedule, 3, 100).unwrap_err(),
+            ChitFundError::InvalidDisbursementSchedule.into()
+        );
+        schedule[..4].copy_from_slice(&[300, 300, 200, 100]);
+        assert_eq!(
+            ChitFund::validate_disbursement_schedule(&schedule, 3, 100).unwrap_err(),
+            ChitFundError::InvalidDisbursementSchedule.into()
+        );
+    }
+
+    #[test]
+    fn only_a_dissolved_fund_settles_an_uncovered_default() {
+        let mut chit_fund = empty_fund();
+        chit_fund.status = FundStatus::Completed;
+        let mut participant = empty_participant();
+        assert!(participant.is_settled(&chit_fund));
+
+        participant.payout_escrow = 10;
+        assert!(!participant.is_settled(&chit_fund));
+
+        participant.payout_escrow = 0;
+        participant.default_shortfall = 10;
+        assert!(!participant.is_settled(&chit_fund));
+
+        chit_fund.status = FundStatus::Dissolved;
+        participant.is_refunded = true;
+        assert!(participant.is_settled(&chit_fund));
+    }
+
+    #[test]
+    fn emergency_approved_while_another_is_pending_waits_its_turn() {
+        let mut chit_fund = empty_fund();
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        chit_fund.participants[..3].copy_from_slice(&members);
+        chit_fund.participants_count = 3;
+        chit_fund.total_cycles = 3;
+
+        assert!(!chit_fund.approve_emergency(members[2]));
+        assert!(chit_fund.approve_emergency(members[1]));
+        assert_eq!(chit_fund.priority_borrower, members[2]);
+
+        chit_fund.mark_borrowed(members[2]);
+        chit_fund.advance_cycle(0);
+        assert_eq!(chit_fund.priority_borrower, members[1]);
+        assert!(!chit_fund.emergency_queue[1]);
+    }
+
+    #[test]
+    fn claimed_and_excluded_dividends_are_not_claimable_again() {
+        let mut chit_fund = empty_fund();
+        chit_fund.dividend_per_member = 50;
+        let mut participant = empty_participant();
+        participant.dividend_excluded = 20;
+        assert_eq!(participant.claimable_dividend(&chit_fund), 30);
+
+        participant.dividends_claimed = 30;
+        assert_eq!(participant.claimable_dividend(&chit_fund), 0);
+
+        chit_fund.dividend_per_member = 60;
+        assert_eq!(participant.claimable_dividend(&chit_fund), 10);
+    }
+
+    #[test]
+    fn holdback_keeps_what_the_winner_still_owes() {
+        let mut chit_fund = empty_fund();
+        chit_fund.contribution_amount = 100;
+        chit_fund.total_cycles = 4;
+        assert_eq!(chit_fund.payout_holdback(400), 0);
+
+        chit_fund.payout_holdback = true;
+        assert_eq!(chit_fund.payout_holdback(400), 300);
+        assert_eq!(chit_fund.payout_holdback(250), 250);
+
+        chit_fund.current_cycle = 3;
+        assert_eq!(chit_fund.payout_holdback(400), 0);
+    }
+
+    #[test]
+    fn payout_order_must_be_a_permutation_of_the_members() {
+        let order = ChitFund::parse_payout_order(&[2, 0, 1], 3).unwrap();
+        assert_eq!(&order[..3], &[2, 0, 1]);
+        assert!(order[3..].iter().all(|&index| index == 0));
+
+        assert!(ChitFund::parse_payout_order(&[0, 1], 3).is_err());
+        assert!(ChitFund::parse_payout_order(&[0, 0, 1], 3).is_err());
+        assert!(ChitFund::parse_payout_order(&[0, 1, 3], 3).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked};
+
+use crate::extensions::transfer_fee;
+
+// One of a fund's token vaults. Each vault is a PDA and its own authority, so it signs
+// transfers out with [seed, chit_fund, bump].
+//
+// Payments out of a vault are sent for the amount recorded, so with a transfer-fee mint the
+// recipient bears the fee. Payments in are grossed up so the pot receives the full amount.
+pub struct VaultSigner<'info> {
+    pub vault: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub decimals: u8,
+    pub token_program: AccountInfo<'info>,
+    pub seed: &'static [u8],
+    pub chit_fund: Pubkey,
+    pub bump: u8,
+}
+
+impl<'info> VaultSigner<'info> {
+    pub fn contribution(
+        vault: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
+        token_program: &Interface<'info, TokenInterface>,
+        chit_fund: Pubkey,
+        bump: u8,
+    ) -> Self {
+        Self::new(vault, mint, token_program, b"contribution_vault", chit_fund, bump)
+    }
+
+    pub fn collateral(
+        vault: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
+        token_program: &Interface<'info, TokenInterface>,
+        chit_fund: Pubkey,
+        bump: u8,
+    ) -> Self {
+        Self::new(vault, mint, token_program, b"collateral_vault", chit_fund, bump)
+    }
+
+    fn new(
+        vault: &InterfaceAccount<'info, TokenAccount>,
+        mint: &InterfaceAccount<'info, Mint>,
+        token_program: &Interface<'info, TokenInterface>,
+        seed: &'static [u8],
+        chit_fund: Pubkey,
+        bump: u8,
+    ) -> Self {
+        Self {
+            vault: vault.to_account_info(),
+            mint: mint.to_account_info(),
+            decimals: mint.decimals,
+            token_program: token_program.to_account_info(),
+            seed,
+            chit_fund,
+            bump,
+        }
+    }
+
+    // Returns the fee the mint withheld from the recipient, for the caller's event
+    pub fn transfer(&self, to: AccountInfo<'info>, amount: u64) -> Result<u64> {
+        let fee = transfer_fee(&self.mint, amount)?;
+        let bump = [self.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[self.seed, self.chit_fund.as_ref(), &bump]];
+
+        let cpi_ctx = CpiContext::new(
+            self.token_program.clone(),
+            TransferChecked {
+                from: self.vault.clone(),
+                to,
+                authority: self.vault.clone(),
+                mint: self.mint.clone(),
+            },
+        ).with_signer(signer_seeds);
+
+        token_interface::transfer_checked(cpi_ctx, amount, self.decimals)?;
+        Ok(fee)
+    }
+
+    // Destroys rounding dust so an emptied vault can be closed without paying it to anyone
+    pub fn burn(&self, amount: u64) -> Result<()> {
+        let bump = [self.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[self.seed, self.chit_fund.as_ref(), &bump]];
+
+        let cpi_ctx = CpiContext::new(
+            self.token_program.clone(),
+            Burn {
+                mint: self.mint.clone(),
+                from: self.vault.clone(),
+                authority: self.vault.clone(),
+            },
+        ).with_signer(signer_seeds);
+
+        token_interface::burn(cpi_ctx, amount)
+    }
+
+    pub fn close(&self, destination: AccountInfo<'info>) -> Result<()> {
+        let bump = [self.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[self.seed, self.chit_fund.as_ref(), &bump]];
+
+        let cpi_ctx = CpiContext::new(
+            self.token_program.clone(),
+            CloseAccount {
+                account: self.vault.clone(),
+                destination,
+                authority: self.vault.clone(),
+            },
+        ).with_signer(signer_seeds);
+
+        token_interface::close_account(cpi_ctx)
+    }
+}
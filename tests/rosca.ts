@@ -1,16 +1,682 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Rosca } from "../target/types/rosca";
-import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY } from '@solana/web3.js';
-import { TOKEN_PROGRAM_ID, createMint } from "@solana/spl-token";
+import { PublicKey, Keypair, SystemProgram, SYSVAR_RENT_PUBKEY, SYSVAR_CLOCK_PUBKEY, SYSVAR_SLOT_HASHES_PUBKEY, LAMPORTS_PER_SOL, Transaction } from '@solana/web3.js';
+import {
+  TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  NATIVE_MINT,
+  ExtensionType,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMint,
+  createWrappedNativeAccount,
+  getAccount,
+  getMintLen,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transferChecked,
+} from "@solana/spl-token";
 import { assert } from "chai";
+import { createHash } from "crypto";
+
+const provider = anchor.AnchorProvider.env();
+anchor.setProvider(provider);
+const program = anchor.workspace.Rosca as Program<Rosca>;
+const payer = (provider.wallet as anchor.Wallet).payer;
+
+const [configPDA] = PublicKey.findProgramAddressSync(
+  [Buffer.from("protocol_config")],
+  program.programId
+);
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+function fundPDAs(fundId: anchor.BN) {
+  const [fund] = PublicKey.findProgramAddressSync(
+    [Buffer.from("chit_fund"), provider.wallet.publicKey.toBuffer(), fundId.toArrayLike(Buffer, "le", 8)],
+    program.programId
+  );
+  const [contributionVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("contribution_vault"), fund.toBuffer()],
+    program.programId
+  );
+  const [collateralVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("collateral_vault"), fund.toBuffer()],
+    program.programId
+  );
+  const [unwrapAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from("unwrap"), fund.toBuffer()],
+    program.programId
+  );
+  return { fund, contributionVault, collateralVault, unwrapAccount };
+}
+
+function participantPDA(fund: PublicKey, owner: PublicKey) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("participant"), fund.toBuffer(), owner.toBuffer()],
+    program.programId
+  )[0];
+}
+
+async function airdrop(to: PublicKey, lamports: number) {
+  const sig = await provider.connection.requestAirdrop(to, lamports);
+  await provider.connection.confirmTransaction(sig);
+}
+
+function schedule(pot: number, cycles: number) {
+  return Array(20).fill(0).map((_, i) => new anchor.BN(i < cycles ? pot : 0));
+}
+
+// Timing checks run against the validator's clock rather than the local one
+async function chainTime() {
+  const clock = await provider.connection.getAccountInfo(SYSVAR_CLOCK_PUBKEY);
+  return Number(clock.data.readBigInt64LE(32));
+}
+
+async function waitUntil(timestamp: number) {
+  while ((await chainTime()) < timestamp) {
+    await sleep(200);
+  }
+}
+
+async function expectError(promise: Promise<unknown>, code: string) {
+  try {
+    await promise;
+  } catch (err) {
+    assert.equal(err.error?.errorCode?.code, code, err.toString());
+    return;
+  }
+  assert.fail(`expected ${code}`);
+}
+
+// Token funds below contribute 100 a cycle against 200 of collateral, both in one SPL mint
+// unless a test says otherwise
+const CONTRIBUTION = 100_000_000;
+const COLLATERAL = 200_000_000;
+
+type Fund = ReturnType<typeof fundPDAs> & {
+  mint: PublicKey;
+  collateralMint: PublicKey;
+  tokenProgram: PublicKey;
+  collateralTokenProgram: PublicKey;
+  priceFeed: PublicKey | null;
+};
+
+// Members of native SOL funds have no token accounts and are paid through the unwrap account
+type Member = { keypair: Keypair; tokenAccount: PublicKey | null; collateralAccount: PublicKey | null };
+
+async function newAllowedMint(tokenProgram = TOKEN_PROGRAM_ID, decimals = 6) {
+  const mint = await createMint(
+    provider.connection,
+    payer,
+    payer.publicKey,
+    null,
+    decimals,
+    Keypair.generate(),
+    undefined,
+    tokenProgram
+  );
+  await program.methods
+    .allowMint()
+    .accountsPartial({ admin: provider.wallet.publicKey, config: configPDA, mint })
+    .rpc();
+  return mint;
+}
+
+// A Token-2022 mint that withholds feeBps of every transfer
+async function newTransferFeeMint(feeBps: number) {
+  const mint = Keypair.generate();
+  const space = getMintLen([ExtensionType.TransferFeeConfig]);
+  const tx = new Transaction().add(
+    SystemProgram.createAccount({
+      fromPubkey: payer.publicKey,
+      newAccountPubkey: mint.publicKey,
+      space,
+      lamports: await provider.connection.getMinimumBalanceForRentExemption(space),
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    createInitializeTransferFeeConfigInstruction(
+      mint.publicKey,
+      payer.publicKey,
+      payer.publicKey,
+      feeBps,
+      BigInt(Number.MAX_SAFE_INTEGER),
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createInitializeMintInstruction(mint.publicKey, 6, payer.publicKey, null, TOKEN_2022_PROGRAM_ID)
+  );
+  await provider.sendAndConfirm(tx, [mint]);
+  await program.methods
+    .allowMint()
+    .accountsPartial({ admin: provider.wallet.publicKey, config: configPDA, mint: mint.publicKey })
+    .rpc();
+  return mint.publicKey;
+}
+
+async function fundedTokenAccount(mint: PublicKey, owner: PublicKey, tokenProgram: PublicKey, amount: number) {
+  const { address } = await getOrCreateAssociatedTokenAccount(
+    provider.connection,
+    payer,
+    mint,
+    owner,
+    false,
+    undefined,
+    undefined,
+    tokenProgram
+  );
+  if (amount > 0) {
+    await mintTo(provider.connection, payer, mint, address, payer, amount, [], undefined, tokenProgram);
+  }
+  return address;
+}
+
+async function newMember(fund: Fund, collateral = 10 * COLLATERAL): Promise<Member> {
+  const keypair = Keypair.generate();
+  await airdrop(keypair.publicKey, LAMPORTS_PER_SOL);
+  const tokenAccount = await fundedTokenAccount(fund.mint, keypair.publicKey, fund.tokenProgram, 10 * COLLATERAL);
+  const collateralAccount = fund.collateralMint.equals(fund.mint)
+    ? tokenAccount
+    : await fundedTokenAccount(fund.collateralMint, keypair.publicKey, fund.collateralTokenProgram, collateral);
+  return { keypair, tokenAccount, collateralAccount };
+}
+
+async function balance(tokenAccount: PublicKey, tokenProgram = TOKEN_PROGRAM_ID) {
+  return Number((await getAccount(provider.connection, tokenAccount, undefined, tokenProgram)).amount);
+}
+
+type FundOptions = {
+  collateralMint?: PublicKey;
+  collateralTokenProgram?: PublicKey;
+  priceFeed?: PublicKey | null;
+  cycles?: number;
+  cycleDuration?: number;
+  selectionMode?: any;
+  maxDiscountBps?: number;
+  deadlineOffset?: number;
+  gracePeriod?: number;
+  lateFeeBps?: number;
+  minParticipants?: number;
+  holdback?: boolean;
+  commissionBps?: number;
+  payoutOrder?: number[];
+  schedule?: anchor.BN[];
+  enrollmentWindow?: number;
+};
+
+function initFundIx(fundId: anchor.BN, mint: PublicKey, options: FundOptions = {}, tokenProgram = TOKEN_PROGRAM_ID) {
+  const cycles = options.cycles ?? 2;
+  const cycleDuration = options.cycleDuration ?? 60;
+  const pdas = fundPDAs(fundId);
+  const collateralMint = options.collateralMint ?? mint;
+  const collateralTokenProgram = options.collateralTokenProgram ?? tokenProgram;
+  const fund: Fund = { ...pdas, mint, collateralMint, tokenProgram, collateralTokenProgram, priceFeed: options.priceFeed ?? null };
+  const builder = program.methods
+    .initChitFund(
+      fundId,
+      new anchor.BN(CONTRIBUTION),
+      new anchor.BN(cycleDuration),
+      cycles,
+      new anchor.BN(COLLATERAL),
+      cycles,
+      options.schedule ?? schedule(cycles * CONTRIBUTION, cycles),
+      options.maxDiscountBps ?? 0,
+      new anchor.BN(options.deadlineOffset ?? cycleDuration),
+      new anchor.BN(options.gracePeriod ?? 0),
+      options.lateFeeBps ?? 0,
+      options.minParticipants ?? cycles,
+      new anchor.BN(Math.floor(Date.now() / 1000) + (options.enrollmentWindow ?? 3600)),
+      options.holdback ?? false,
+      options.commissionBps ?? 0,
+      options.selectionMode ?? { lottery: {} },
+      Buffer.from(options.payoutOrder ?? [])
+    )
+    .accountsPartial({
+      creator: provider.wallet.publicKey,
+      contributionVault: pdas.contributionVault,
+      collateralVault: pdas.collateralVault,
+      chitFund: pdas.fund,
+      config: configPDA,
+      mint,
+      collateralMint,
+      priceFeed: fund.priceFeed,
+      tokenProgram,
+      collateralTokenProgram,
+    });
+  return { fund, builder };
+}
+
+async function initFund(fundId: anchor.BN, mint: PublicKey, options: FundOptions = {}, tokenProgram = TOKEN_PROGRAM_ID) {
+  const { fund, builder } = initFundIx(fundId, mint, options, tokenProgram);
+  await builder.rpc();
+  return fund;
+}
+
+function joinIx(fund: Fund, member: Member) {
+  return program.methods
+    .joinChit()
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      mint: fund.mint,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      collateralVault: fund.collateralVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      userTokenAccount: member.tokenAccount,
+      userCollateralAccount: member.collateralAccount,
+      priceFeed: fund.priceFeed,
+      tokenProgram: fund.tokenProgram,
+      collateralTokenProgram: fund.collateralTokenProgram,
+    })
+    .signers([member.keypair]);
+}
+
+async function join(fund: Fund, member: Member) {
+  await joinIx(fund, member).rpc();
+}
+
+function contributeIx(fund: Fund, member: Member) {
+  return program.methods
+    .contribution()
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      mint: fund.mint,
+      chitFund: fund.fund,
+      contributionVault: fund.contributionVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      userTokenAccount: member.tokenAccount,
+      tokenProgram: fund.tokenProgram,
+    })
+    .signers([member.keypair]);
+}
+
+async function contribute(fund: Fund, member: Member) {
+  await contributeIx(fund, member).rpc();
+}
+
+async function startFund(fund: Fund, payoutOrder: number[] = []) {
+  await program.methods
+    .startFund(Buffer.from(payoutOrder))
+    .accountsPartial({ creator: provider.wallet.publicKey, chitFund: fund.fund })
+    .rpc();
+}
+
+// Sets up a fund with one member per cycle, started and with cycle 0 fully paid
+async function activeFund(fundId: anchor.BN, mint: PublicKey, options: FundOptions = {}, tokenProgram = TOKEN_PROGRAM_ID) {
+  const fund = await initFund(fundId, mint, options, tokenProgram);
+  const members: Member[] = [];
+  for (let i = 0; i < (options.cycles ?? 2); i++) {
+    const member = await newMember(fund);
+    await join(fund, member);
+    members.push(member);
+  }
+  await startFund(fund);
+  for (const member of members) {
+    await contribute(fund, member);
+  }
+  return { fund, members };
+}
+
+async function cycleTimes(fund: Fund) {
+  const chitFund = await program.account.chitFund.fetch(fund.fund);
+  const start = chitFund.lastDisbursementTime.toNumber();
+  return {
+    deadline: start + chitFund.contributionDeadlineOffset.toNumber(),
+    graceEnd: start + chitFund.contributionDeadlineOffset.toNumber() + chitFund.gracePeriod.toNumber(),
+    end: start + chitFund.cycleDuration.toNumber(),
+  };
+}
+
+type FeeAccounts = { creator?: PublicKey; treasury?: PublicKey };
+
+function claimPayoutIx(fund: Fund, member: Member, fees: FeeAccounts = {}) {
+  return program.methods
+    .claimPayout()
+    .accountsPartial({
+      user: provider.wallet.publicKey,
+      mint: fund.mint,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      contributionVault: fund.contributionVault,
+      collateralVault: fund.collateralVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      borrowerTokenAccount: member.tokenAccount,
+      borrowerCollateralAccount: member.collateralAccount,
+      borrowerWallet: null,
+      unwrapAccount: null,
+      creatorFeeAccount: fees.creator ?? null,
+      treasuryFeeAccount: fees.treasury ?? null,
+      tokenProgram: fund.tokenProgram,
+      collateralTokenProgram: fund.collateralTokenProgram,
+    });
+}
+
+// Commitments are sha256(seed || owner), so nobody can copy another member's
+function commitment(seed: Buffer, owner: PublicKey) {
+  return Array.from(createHash("sha256").update(Buffer.concat([seed, owner.toBuffer()])).digest());
+}
+
+function commitSeedIx(fund: Fund, member: Member, seed: Buffer) {
+  return program.methods
+    .commitSeed(commitment(seed, member.keypair.publicKey))
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      chitFund: fund.fund,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+    })
+    .signers([member.keypair]);
+}
+
+function revealSeedIx(fund: Fund, member: Member, seed: Buffer) {
+  return program.methods
+    .revealSeed(Array.from(seed))
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      chitFund: fund.fund,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+    })
+    .signers([member.keypair]);
+}
+
+function drawWinnerIx(fund: Fund) {
+  return program.methods.drawWinner().accountsPartial({
+    user: provider.wallet.publicKey,
+    chitFund: fund.fund,
+    recentSlothashes: SYSVAR_SLOT_HASHES_PUBKEY,
+  });
+}
+
+function auctionPDA(fund: Fund, cycle: number) {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("auction"), fund.fund.toBuffer(), Buffer.from([cycle])],
+    program.programId
+  )[0];
+}
+
+function placeBidIx(fund: Fund, member: Member, discount: number, cycle = 0) {
+  return program.methods
+    .placeBid(new anchor.BN(discount))
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      chitFund: fund.fund,
+      auction: auctionPDA(fund, cycle),
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+    })
+    .signers([member.keypair]);
+}
+
+async function closeAuction(fund: Fund, cycle = 0) {
+  await program.methods
+    .closeAuction()
+    .accountsPartial({ user: provider.wallet.publicKey, chitFund: fund.fund, auction: auctionPDA(fund, cycle) })
+    .rpc();
+}
+
+function settleAuctionIx(fund: Fund, winner: Member, cycle = 0, fees: FeeAccounts = {}) {
+  const native = winner.tokenAccount === null;
+  return program.methods
+    .settleAuction()
+    .accountsPartial({
+      user: provider.wallet.publicKey,
+      mint: fund.mint,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      auction: auctionPDA(fund, cycle),
+      contributionVault: fund.contributionVault,
+      collateralVault: fund.collateralVault,
+      winnerParticipant: participantPDA(fund.fund, winner.keypair.publicKey),
+      winnerTokenAccount: winner.tokenAccount,
+      winnerCollateralAccount: winner.collateralAccount,
+      winnerWallet: native ? winner.keypair.publicKey : null,
+      unwrapAccount: native ? fund.unwrapAccount : null,
+      creatorFeeAccount: fees.creator ?? null,
+      treasuryFeeAccount: fees.treasury ?? null,
+      tokenProgram: fund.tokenProgram,
+      collateralTokenProgram: fund.collateralTokenProgram,
+    });
+}
+
+function claimDividendIx(fund: Fund, member: Member) {
+  return program.methods
+    .claimDividend()
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      mint: fund.mint,
+      chitFund: fund.fund,
+      contributionVault: fund.contributionVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      userTokenAccount: member.tokenAccount,
+      unwrapAccount: member.tokenAccount === null ? fund.unwrapAccount : null,
+      tokenProgram: fund.tokenProgram,
+    })
+    .signers([member.keypair]);
+}
+
+function markDefaultIx(fund: Fund, member: Member) {
+  return program.methods.markDefault().accountsPartial({
+    user: provider.wallet.publicKey,
+    mint: fund.mint,
+    collateralMint: fund.collateralMint,
+    chitFund: fund.fund,
+    participant: participantPDA(fund.fund, member.keypair.publicKey),
+    priceFeed: fund.priceFeed,
+  });
+}
+
+function requestEmergencyIx(fund: Fund, member: Member) {
+  return program.methods
+    .requestEmergency(1)
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      chitFund: fund.fund,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+    })
+    .signers([member.keypair]);
+}
+
+// Votes are keyed by how many requests the member has made, so each request gets fresh votes
+async function voteEmergencyIx(fund: Fund, voter: Member, requester: Member, approve: boolean) {
+  const requesterPDA = participantPDA(fund.fund, requester.keypair.publicKey);
+  const { emergencyRequests } = await program.account.participant.fetch(requesterPDA);
+  const [vote] = PublicKey.findProgramAddressSync(
+    [Buffer.from("emergency_vote"), requesterPDA.toBuffer(), Buffer.from([emergencyRequests]), voter.keypair.publicKey.toBuffer()],
+    program.programId
+  );
+  return program.methods
+    .voteEmergency(approve)
+    .accountsPartial({
+      user: voter.keypair.publicKey,
+      chitFund: fund.fund,
+      voterParticipant: participantPDA(fund.fund, voter.keypair.publicKey),
+      requesterParticipant: requesterPDA,
+      vote,
+    })
+    .signers([voter.keypair]);
+}
+
+function cancelFundIx(fund: Fund, user: Keypair = payer) {
+  return program.methods
+    .cancelFund()
+    .accountsPartial({ user: user.publicKey, chitFund: fund.fund })
+    .signers(user === payer ? [] : [user]);
+}
+
+function withdrawIx(fund: Fund, member: Member) {
+  return program.methods
+    .withdraw()
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      collateralVault: fund.collateralVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      userCollateralAccount: member.collateralAccount,
+      unwrapAccount: member.collateralAccount === null ? fund.unwrapAccount : null,
+      collateralTokenProgram: fund.collateralTokenProgram,
+    })
+    .signers([member.keypair]);
+}
+
+function closeParticipantIx(fund: Fund, member: Member) {
+  return program.methods
+    .closeParticipant()
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      chitFund: fund.fund,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+    })
+    .signers([member.keypair]);
+}
+
+function closeFundIx(fund: Fund) {
+  return program.methods.closeFund().accountsPartial({
+    creator: provider.wallet.publicKey,
+    chitFund: fund.fund,
+    mint: fund.mint,
+    collateralMint: fund.collateralMint,
+    contributionVault: fund.contributionVault,
+    collateralVault: fund.collateralVault,
+    tokenProgram: fund.tokenProgram,
+    collateralTokenProgram: fund.collateralTokenProgram,
+  });
+}
+
+// Runs a two member fixed-order fund to completion
+async function completedFund(fundId: anchor.BN) {
+  const mint = await newAllowedMint();
+  const { fund, members } = await activeFund(fundId, mint, {
+    cycleDuration: 3,
+    selectionMode: { fixedOrder: {} },
+    payoutOrder: [0, 1],
+  });
+  await waitUntil((await cycleTimes(fund)).end);
+  await claimPayoutIx(fund, members[0]).rpc();
+  for (const member of members) {
+    await contribute(fund, member);
+  }
+  await waitUntil((await cycleTimes(fund)).end);
+  await claimPayoutIx(fund, members[1]).rpc();
+  return { fund, members };
+}
+
+// Completes a fund and settles both members
+async function settledFund(fundId: anchor.BN) {
+  const { fund, members } = await completedFund(fundId);
+  for (const member of members) {
+    await withdrawIx(fund, member).rpc();
+    await closeParticipantIx(fund, member).rpc();
+  }
+  return { fund, members };
+}
+
+function claimRefundIx(fund: Fund, member: Member) {
+  const native = member.tokenAccount === null || member.collateralAccount === null;
+  return program.methods
+    .claimRefund()
+    .accountsPartial({
+      user: member.keypair.publicKey,
+      mint: fund.mint,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      collateralVault: fund.collateralVault,
+      contributionVault: fund.contributionVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      userTokenAccount: member.tokenAccount,
+      userCollateralAccount: member.collateralAccount,
+      unwrapAccount: native ? fund.unwrapAccount : null,
+      tokenProgram: fund.tokenProgram,
+      collateralTokenProgram: fund.collateralTokenProgram,
+    })
+    .signers([member.keypair]);
+}
+
+function dissolveFundIx(fund: Fund, members: Member[]) {
+  return program.methods
+    .dissolveFund()
+    .accountsPartial({
+      user: provider.wallet.publicKey,
+      mint: fund.mint,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      priceFeed: fund.priceFeed,
+    })
+    .remainingAccounts(
+      members.map((member) => ({
+        pubkey: participantPDA(fund.fund, member.keypair.publicKey),
+        isWritable: true,
+        isSigner: false,
+      }))
+    );
+}
+
+async function allowNativeMint() {
+  const config = await program.account.protocolConfig.fetch(configPDA);
+  if (!config.allowedMints.some((mint: PublicKey) => mint.equals(NATIVE_MINT))) {
+    await program.methods
+      .allowMint()
+      .accountsPartial({ admin: provider.wallet.publicKey, config: configPDA, mint: NATIVE_MINT })
+      .rpc();
+  }
+}
+
+async function setMintPolicy(allowTransferFee: boolean) {
+  await program.methods
+    .setMintPolicy(false, false, false, allowTransferFee)
+    .accountsPartial({ admin: provider.wallet.publicKey, config: configPDA })
+    .rpc();
+}
+
+async function setProtocolFee(protocolFeeBps: number) {
+  await program.methods
+    .updateConfig(protocolFeeBps, 20, 20, new anchor.BN(1), 500)
+    .accountsPartial({ admin: provider.wallet.publicKey, config: configPDA })
+    .rpc();
+}
+
+// Test oracle prices are only honoured by builds with the test-oracle feature:
+// anchor test -- --features test-oracle
+const [testOraclePDA] = PublicKey.findProgramAddressSync(
+  [Buffer.from("test_oracle"), provider.wallet.publicKey.toBuffer()],
+  program.programId
+);
+
+async function setTestPrice(price: number, expo: number) {
+  try {
+    await program.methods
+      .setTestOracle(new anchor.BN(price), expo)
+      .accountsPartial({ authority: provider.wallet.publicKey, oracle: testOraclePDA })
+      .rpc();
+    return true;
+  } catch (err) {
+    if (err.error?.errorCode?.code === "TestOracleDisabled") {
+      return false;
+    }
+    throw err;
+  }
+}
+
+before(async () => {
+  const [programDataPDA] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  await program.methods
+    .initProtocolConfig(provider.wallet.publicKey, 0, 20, 20, new anchor.BN(1), 500)
+    .accounts({
+      admin: provider.wallet.publicKey,
+      config: configPDA,
+      program: program.programId,
+      programData: programDataPDA,
+      systemProgram: SystemProgram.programId,
+    })
+    .rpc();
+});
 
 describe("Initialize ChitFund", () => {
-  const provider = anchor.AnchorProvider.env();
-  anchor.setProvider(provider);
-  const program = anchor.workspace.Rosca as Program<Rosca>;
   let signer = anchor.web3.Keypair.generate();
 
+  const fundId = new anchor.BN(0);
   let mint: PublicKey;
   let fundPDA: PublicKey;
   let contributionVaultPDA: PublicKey;
@@ -18,57 +684,1098 @@ describe("Initialize ChitFund", () => {
 
   before(async () => {
     // Fund signer
-    const airdropSig = await provider.connection.requestAirdrop(signer.publicKey, 1000000000);
-    await provider.connection.confirmTransaction(airdropSig);
+    await airdrop(signer.publicKey, 1000000000);
 
     mint = await createMint(
       provider.connection,
       signer,
       provider.wallet.publicKey,
-      provider.wallet.publicKey,
+      null,
       6
     );
 
-    [fundPDA] = PublicKey.findProgramAddressSync(
-      [mint.toBuffer()],
-      program.programId
-    );
-
-    [contributionVaultPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("contribution_vault"), mint.toBuffer()],
-      program.programId
-    );
+    ({ fund: fundPDA, contributionVault: contributionVaultPDA, collateralVault: collateralVaultPDA } = fundPDAs(fundId));
 
-    [collateralVaultPDA] = PublicKey.findProgramAddressSync(
-      [Buffer.from("collateral_vault"), mint.toBuffer()],
-      program.programId
-    );
+    await program.methods
+      .allowMint()
+      .accounts({
+        admin: provider.wallet.publicKey,
+        config: configPDA,
+        mint,
+      })
+      .rpc();
   });
 
   it("Initialize Fund", async () => {
     const tx = await program.methods
       .initChitFund(
+        fundId,
         new anchor.BN(100_000_000),
         new anchor.BN(5),
         4,
         new anchor.BN(200_000_000),
         4,
-        Array(4).fill(400_000_000).map(x => new anchor.BN(x))
+        schedule(400_000_000, 4),
+        0,
+        new anchor.BN(5),
+        new anchor.BN(0),
+        0,
+        4,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        false,
+        0,
+        { lottery: {} },
+        Buffer.from([])
       )
       .accounts({
         creator: provider.wallet.publicKey,
         contributionVault: contributionVaultPDA,
         collateralVault: collateralVaultPDA,
         chitFund: fundPDA,
+        config: configPDA,
         mint,
+        collateralMint: mint,
+        priceFeed: null,
         tokenProgram: TOKEN_PROGRAM_ID,
+        collateralTokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: SystemProgram.programId,
         rent: SYSVAR_RENT_PUBKEY
       })
       .rpc();
 
     const fund = await program.account.chitFund.fetch(fundPDA);
-    assert.equal(fund.isActive, true);
+    assert.deepEqual(fund.status, { enrolling: {} });
     assert.equal(fund.currentCycle, 0);
+    assert.ok(fund.fundId.eq(fundId));
+  });
+
+  it("Requires a full roster to start", async () => {
+    // With fewer than one member per cycle the fund could neither start nor fail enrollment
+    const { builder } = initFundIx(new anchor.BN(110), mint, { cycles: 4, minParticipants: 2 });
+    await expectError(builder.rpc(), "InvalidMinParticipants");
+  });
+
+  it("Requires a price feed for collateral in another mint", async () => {
+    const collateralMint = await newAllowedMint(TOKEN_2022_PROGRAM_ID, 9);
+    const { builder } = initFundIx(new anchor.BN(161), mint, {
+      collateralMint,
+      collateralTokenProgram: TOKEN_2022_PROGRAM_ID,
+    });
+    await expectError(builder.rpc(), "PriceFeedRequired");
+  });
+});
+
+describe("Native SOL fund", () => {
+  const fundId = new anchor.BN(1);
+  const members = [Keypair.generate(), Keypair.generate()];
+  const pdas = fundPDAs(fundId);
+  const contribution = 100_000_000;
+  let creatorWsol: PublicKey;
+
+  before(async () => {
+    for (const member of members) {
+      await airdrop(member.publicKey, 2 * LAMPORTS_PER_SOL);
+    }
+
+    await allowNativeMint();
+
+    // Creator and treasury are the same wallet here, so one wSOL account takes both fees
+    creatorWsol = await createWrappedNativeAccount(provider.connection, payer, provider.wallet.publicKey, 0);
+
+    await program.methods
+      .initChitFund(
+        fundId,
+        new anchor.BN(contribution),
+        new anchor.BN(2),
+        2,
+        new anchor.BN(2 * contribution),
+        2,
+        schedule(2 * contribution, 2),
+        0,
+        new anchor.BN(2),
+        new anchor.BN(0),
+        0,
+        4,
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        false,
+        0,
+        { fixedOrder: {} },
+        Buffer.from([])
+      )
+      .accountsPartial({
+        creator: provider.wallet.publicKey,
+        contributionVault: pdas.contributionVault,
+        collateralVault: pdas.collateralVault,
+        chitFund: pdas.fund,
+        config: configPDA,
+        mint: NATIVE_MINT,
+        collateralMint: NATIVE_MINT,
+        priceFeed: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        collateralTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    // Members join and pay in lamports without any token account
+    for (const member of members) {
+      await program.methods
+        .joinChit()
+        .accountsPartial({
+          user: member.publicKey,
+          mint: NATIVE_MINT,
+          collateralMint: NATIVE_MINT,
+          chitFund: pdas.fund,
+          collateralVault: pdas.collateralVault,
+          participant: participantPDA(pdas.fund, member.publicKey),
+          userTokenAccount: null,
+          userCollateralAccount: null,
+          priceFeed: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          collateralTokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([member])
+        .rpc();
+    }
+
+    await program.methods
+      .startFund(Buffer.from([]))
+      .accountsPartial({ creator: provider.wallet.publicKey, chitFund: pdas.fund })
+      .rpc();
+
+    for (const member of members) {
+      await program.methods
+        .contribution()
+        .accountsPartial({
+          user: member.publicKey,
+          mint: NATIVE_MINT,
+          chitFund: pdas.fund,
+          contributionVault: pdas.contributionVault,
+          participant: participantPDA(pdas.fund, member.publicKey),
+          userTokenAccount: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([member])
+        .rpc();
+    }
+  });
+
+  it("Pays out in SOL even when the unwrap address was funded beforehand", async () => {
+    // Anyone can send lamports to the unwrap address ahead of the payout
+    const stray = await provider.connection.getMinimumBalanceForRentExemption(0);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: pdas.unwrapAccount,
+          lamports: stray,
+        })
+      )
+    );
+
+    await sleep(3000);
+
+    const winner = members[0];
+    const balanceBefore = await provider.connection.getBalance(winner.publicKey);
+
+    await program.methods
+      .claimPayout()
+      .accountsPartial({
+        user: provider.wallet.publicKey,
+        mint: NATIVE_MINT,
+        collateralMint: NATIVE_MINT,
+        chitFund: pdas.fund,
+        contributionVault: pdas.contributionVault,
+        collateralVault: pdas.collateralVault,
+        participant: participantPDA(pdas.fund, winner.publicKey),
+        borrowerTokenAccount: null,
+        borrowerCollateralAccount: null,
+        borrowerWallet: winner.publicKey,
+        unwrapAccount: pdas.unwrapAccount,
+        creatorFeeAccount: creatorWsol,
+        treasuryFeeAccount: creatorWsol,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        collateralTokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const balanceAfter = await provider.connection.getBalance(winner.publicKey);
+    assert.equal(balanceAfter - balanceBefore, 2 * contribution);
+    assert.isNull(await provider.connection.getAccountInfo(pdas.unwrapAccount));
+
+    const fund = await program.account.chitFund.fetch(pdas.fund);
+    assert.equal(fund.currentCycle, 1);
+  });
+});
+
+describe("Collateral in another mint", () => {
+  let fund: Fund;
+  let member: Member;
+
+  before(async function () {
+    // One collateral token is worth two contribution tokens
+    if (!(await setTestPrice(2, 0))) {
+      this.skip();
+    }
+
+    const mint = await newAllowedMint(TOKEN_PROGRAM_ID, 6);
+    const collateralMint = await newAllowedMint(TOKEN_2022_PROGRAM_ID, 9);
+    fund = await initFund(new anchor.BN(160), mint, {
+      collateralMint,
+      collateralTokenProgram: TOKEN_2022_PROGRAM_ID,
+      priceFeed: testOraclePDA,
+    });
+    member = await newMember(fund, 1_000 * 10 ** 9);
+  });
+
+  it("Posts collateral worth the requirement through the collateral mint's own token program", async () => {
+    await join(fund, member);
+
+    // 200 contribution tokens at two apiece is 100 collateral tokens
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, member.keypair.publicKey));
+    assert.equal(participant.collateralDeposited.toString(), (100 * 10 ** 9).toString());
+    assert.equal(await balance(fund.collateralVault, TOKEN_2022_PROGRAM_ID), 100 * 10 ** 9);
+    assert.equal(await balance(member.collateralAccount, TOKEN_2022_PROGRAM_ID), 900 * 10 ** 9);
+  });
+});
+
+describe("Foreman commission", () => {
+  let fund: Fund;
+  let members: Member[];
+  let creatorAccount: PublicKey;
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(200), mint, {
+      cycleDuration: 3,
+      selectionMode: { fixedOrder: {} },
+      payoutOrder: [0, 1],
+      commissionBps: 500,
+    }));
+    creatorAccount = await fundedTokenAccount(mint, provider.wallet.publicKey, TOKEN_PROGRAM_ID, 0);
+    await waitUntil((await cycleTimes(fund)).end);
+  });
+
+  it("Needs the creator's token account when a commission is due", async () => {
+    await expectError(
+      claimPayoutIx(fund, members[0]).rpc(),
+      "InvalidCreatorFeeAccount"
+    );
+  });
+
+  it("Pays the foreman their cut of the pot", async () => {
+    const creatorBefore = await balance(creatorAccount);
+    const winnerBefore = await balance(members[0].tokenAccount);
+    await claimPayoutIx(fund, members[0], { creator: creatorAccount }).rpc();
+
+    // 5% of the 200 pot
+    assert.equal((await balance(creatorAccount)) - creatorBefore, 10_000_000);
+    assert.equal((await balance(members[0].tokenAccount)) - winnerBefore, 190_000_000);
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(chitFund.foremanCommissionsPaid.toNumber(), 10_000_000);
+  });
+});
+
+describe("Protocol fee", () => {
+  let fund: Fund;
+  let members: Member[];
+  let treasuryAccount: PublicKey;
+
+  before(async () => {
+    const mint = await newAllowedMint();
+
+    // The fee is fixed into the fund at init, so the config can go straight back to zero
+    await setProtocolFee(100);
+    try {
+      ({ fund, members } = await activeFund(new anchor.BN(210), mint, {
+        cycleDuration: 3,
+        selectionMode: { fixedOrder: {} },
+        payoutOrder: [0, 1],
+      }));
+    } finally {
+      await setProtocolFee(0);
+    }
+    treasuryAccount = await fundedTokenAccount(mint, provider.wallet.publicKey, TOKEN_PROGRAM_ID, 0);
+    await waitUntil((await cycleTimes(fund)).end);
+  });
+
+  it("Needs the treasury's token account when a fee is due", async () => {
+    await expectError(claimPayoutIx(fund, members[0]).rpc(), "InvalidTreasuryAccount");
+  });
+
+  it("Pays the treasury its fee and keeps the foreman's account optional", async () => {
+    const treasuryBefore = await balance(treasuryAccount);
+    await claimPayoutIx(fund, members[0], { treasury: treasuryAccount }).rpc();
+
+    // 1% of the 200 pot
+    assert.equal((await balance(treasuryAccount)) - treasuryBefore, 2_000_000);
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(chitFund.protocolFeesPaid.toNumber(), 2_000_000);
+  });
+});
+
+describe("Native SOL dividends", () => {
+  const discount = 10_000_000;
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    await allowNativeMint();
+    fund = await initFund(new anchor.BN(240), NATIVE_MINT, {
+      cycleDuration: 3,
+      selectionMode: { auction: {} },
+      maxDiscountBps: 1_000,
+    });
+
+    members = [];
+    for (let i = 0; i < 2; i++) {
+      const keypair = Keypair.generate();
+      await airdrop(keypair.publicKey, 2 * LAMPORTS_PER_SOL);
+      const member = { keypair, tokenAccount: null, collateralAccount: null };
+      await join(fund, member);
+      members.push(member);
+    }
+    await startFund(fund);
+    for (const member of members) {
+      await contribute(fund, member);
+    }
+
+    await placeBidIx(fund, members[0], discount).rpc();
+    await waitUntil((await cycleTimes(fund)).end);
+    await closeAuction(fund);
+    await settleAuctionIx(fund, members[0]).rpc();
+  });
+
+  it("Claims a dividend straight to the wallet without a token account", async () => {
+    // The provider pays the transaction fee and the unwrap account's rent comes back,
+    // so the wallet grows by exactly the dividend
+    const balanceBefore = await provider.connection.getBalance(members[1].keypair.publicKey);
+    await claimDividendIx(fund, members[1]).rpc();
+
+    const balanceAfter = await provider.connection.getBalance(members[1].keypair.publicKey);
+    assert.equal(balanceAfter - balanceBefore, discount);
+    assert.isNull(await provider.connection.getAccountInfo(fund.unwrapAccount));
+  });
+});
+
+describe("Dissolved fund", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(130), mint));
+  });
+
+  it("Settles from the fund's ledger, not tokens sent to the vault", async () => {
+    // Anyone can send tokens to the vault; they must not inflate the settlement
+    const donor = await newMember(fund);
+    await transferChecked(
+      provider.connection,
+      payer,
+      donor.tokenAccount,
+      fund.mint,
+      fund.contributionVault,
+      donor.keypair,
+      CONTRIBUTION,
+      6
+    );
+
+    await dissolveFundIx(fund, members).rpc();
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.deepEqual(chitFund.status, { dissolved: {} });
+
+    // Nobody has been paid yet, so each gets their contribution and collateral back
+    for (const member of members) {
+      const participant = await program.account.participant.fetch(participantPDA(fund.fund, member.keypair.publicKey));
+      assert.equal(participant.settlementAmount.toNumber(), CONTRIBUTION);
+
+      const balanceBefore = await balance(member.tokenAccount);
+      await claimRefundIx(fund, member).rpc();
+      assert.equal((await balance(member.tokenAccount)) - balanceBefore, CONTRIBUTION + COLLATERAL);
+    }
+
+    const settled = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(settled.totalContributionAmount.toNumber(), 0);
+    assert.equal(settled.totalCollateralAmount.toNumber(), 0);
+  });
+
+  it("Refuses a second refund", async () => {
+    await expectError(claimRefundIx(fund, members[0]).rpc(), "RefundAlreadyClaimed");
+  });
+});
+
+describe("Closing a fund", () => {
+  it("Rejects a schedule that does not pay out every contribution", async () => {
+    const mint = await newAllowedMint();
+    const { builder } = initFundIx(new anchor.BN(140), mint, { schedule: schedule(CONTRIBUTION, 2) });
+    await expectError(builder.rpc(), "InvalidDisbursementTotal");
+  });
+
+  it("Closes a settled fund and returns the rent to the creator", async () => {
+    const { fund } = await settledFund(new anchor.BN(141));
+    await closeFundIx(fund).rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(fund.fund));
+    assert.isNull(await provider.connection.getAccountInfo(fund.contributionVault));
+    assert.isNull(await provider.connection.getAccountInfo(fund.collateralVault));
+  });
+
+  it("Refuses to close while a vault holds more than rounding dust", async () => {
+    const { fund } = await settledFund(new anchor.BN(142));
+
+    // Nothing is swept to the creator, so the tokens keep the fund open
+    const donor = await newMember(fund);
+    await transferChecked(
+      provider.connection,
+      payer,
+      donor.tokenAccount,
+      fund.mint,
+      fund.contributionVault,
+      donor.keypair,
+      CONTRIBUTION,
+      6
+    );
+    await expectError(closeFundIx(fund).rpc(), "ChitFundNotSettled");
+  });
+
+  it("Returns the rent for an auction and the votes cast once they are done with", async () => {
+    const mint = await newAllowedMint();
+    const { fund, members } = await activeFund(new anchor.BN(143), mint, {
+      cycleDuration: 3,
+      selectionMode: { auction: {} },
+      maxDiscountBps: 1_000,
+    });
+    const [dissolveVote] = PublicKey.findProgramAddressSync(
+      [Buffer.from("dissolve_vote"), fund.fund.toBuffer(), members[0].keypair.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await placeBidIx(fund, members[0], 0).rpc();
+    await program.methods
+      .voteDissolve()
+      .accountsPartial({
+        user: members[0].keypair.publicKey,
+        chitFund: fund.fund,
+        participant: participantPDA(fund.fund, members[0].keypair.publicKey),
+        vote: dissolveVote,
+      })
+      .signers([members[0].keypair])
+      .rpc();
+
+    const closeAuctionAccountIx = () =>
+      program.methods
+        .closeAuctionAccount()
+        .accountsPartial({ user: members[0].keypair.publicKey, chitFund: fund.fund, auction: auctionPDA(fund, 0) })
+        .signers([members[0].keypair]);
+    const closeDissolveVoteIx = () =>
+      program.methods
+        .closeDissolveVote()
+        .accountsPartial({ user: members[0].keypair.publicKey, chitFund: fund.fund, vote: dissolveVote })
+        .signers([members[0].keypair]);
+
+    // Both still decide something while the cycle and the fund run
+    await expectError(closeAuctionAccountIx().rpc(), "AccountStillInUse");
+    await expectError(closeDissolveVoteIx().rpc(), "AccountStillInUse");
+
+    await waitUntil((await cycleTimes(fund)).end);
+    await closeAuction(fund);
+    await settleAuctionIx(fund, members[0]).rpc();
+    await closeAuctionAccountIx().rpc();
+    assert.isNull(await provider.connection.getAccountInfo(auctionPDA(fund, 0)));
+
+    await dissolveFundIx(fund, members).rpc();
+    await closeDissolveVoteIx().rpc();
+    assert.isNull(await provider.connection.getAccountInfo(dissolveVote));
+  });
+});
+
+describe("Lottery fund", () => {
+  const seeds = [Buffer.alloc(32, 1), Buffer.alloc(32, 2)];
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(50), mint, { cycleDuration: 3 }));
+    for (const [i, member] of members.entries()) {
+      await commitSeedIx(fund, member, seeds[i]).rpc();
+    }
+  });
+
+  it("Waits for every committed seed before drawing", async () => {
+    await expectError(drawWinnerIx(fund).rpc(), "CycleNotComplete");
+
+    await waitUntil((await cycleTimes(fund)).end);
+    await revealSeedIx(fund, members[0], seeds[0]).rpc();
+    await expectError(drawWinnerIx(fund).rpc(), "RevealsPending");
+  });
+
+  it("Rejects a seed that does not match the commitment", async () => {
+    await expectError(revealSeedIx(fund, members[1], seeds[0]).rpc(), "InvalidReveal");
+  });
+
+  it("Draws the winner from the revealed seeds alone and pays them", async () => {
+    await revealSeedIx(fund, members[1], seeds[1]).rpc();
+    await drawWinnerIx(fund).rpc();
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    const winner = members.find((member) => member.keypair.publicKey.equals(chitFund.selectedBorrower));
+    assert.isDefined(winner);
+
+    // The same seeds always draw the same winner, so the slot hash played no part
+    const drawSeed = [seeds[0], seeds[1]].reduce(
+      (acc, seed) => createHash("sha256").update(Buffer.concat([acc, seed])).digest(),
+      Buffer.alloc(32)
+    );
+    const expected = Number(drawSeed.readBigUInt64LE(0) % BigInt(members.length));
+    assert.equal(winner, members[expected]);
+
+    const balanceBefore = await balance(winner.tokenAccount);
+    await claimPayoutIx(fund, winner).rpc();
+    assert.equal((await balance(winner.tokenAccount)) - balanceBefore, 2 * CONTRIBUTION);
+  });
+});
+
+describe("Missed contribution", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    fund = await initFund(new anchor.BN(80), mint, { cycleDuration: 60, deadlineOffset: 3 });
+    members = [await newMember(fund), await newMember(fund)];
+    for (const member of members) {
+      await join(fund, member);
+    }
+    await startFund(fund);
+    await contribute(fund, members[0]);
+  });
+
+  it("Cannot be marked before the deadline", async () => {
+    await expectError(markDefaultIx(fund, members[1]).rpc(), "ContributionWindowOpen");
+  });
+
+  it("Is covered from the member's collateral", async () => {
+    await waitUntil((await cycleTimes(fund)).graceEnd);
+    await markDefaultIx(fund, members[1]).rpc();
+
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, members[1].keypair.publicKey));
+    assert.isTrue(participant.defaults[0]);
+    assert.isTrue(participant.contributions[0]);
+    assert.equal(participant.collateralSlashed.toNumber(), CONTRIBUTION);
+    assert.equal(participant.defaultShortfall.toNumber(), 0);
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(chitFund.defaultCount, 1);
+    assert.equal(chitFund.seizedCollateral.toNumber(), CONTRIBUTION);
+  });
+
+  it("Cannot be marked twice", async () => {
+    await expectError(markDefaultIx(fund, members[1]).rpc(), "ContributionAlreadyMade");
+    await expectError(markDefaultIx(fund, members[0]).rpc(), "ContributionAlreadyMade");
+  });
+});
+
+describe("Auction fund", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(30), mint, {
+      cycleDuration: 3,
+      selectionMode: { auction: {} },
+      maxDiscountBps: 1_000,
+    }));
+  });
+
+  it("Rejects a discount above the fund's cap", async () => {
+    // 10% of the 200 pot
+    await expectError(placeBidIx(fund, members[0], 20_000_001).rpc(), "DiscountTooHigh");
+  });
+
+  it("Keeps the highest discount, with ties going to the earlier bid", async () => {
+    await placeBidIx(fund, members[0], 5_000_000).rpc();
+    await placeBidIx(fund, members[1], 10_000_000).rpc();
+    await placeBidIx(fund, members[0], 10_000_000).rpc();
+
+    const auction = await program.account.cycleAuction.fetch(auctionPDA(fund, 0));
+    assert.isTrue(auction.bestBidder.equals(members[1].keypair.publicKey));
+    assert.equal(auction.bestDiscount.toNumber(), 10_000_000);
+    assert.equal(auction.bidCount, 3);
+  });
+
+  it("Pays the winner the pot less their discount", async () => {
+    await expectError(closeAuction(fund), "CycleNotComplete");
+    await waitUntil((await cycleTimes(fund)).end);
+    await expectError(placeBidIx(fund, members[0], 15_000_000).rpc(), "BiddingClosed");
+    await closeAuction(fund);
+
+    const balanceBefore = await balance(members[1].tokenAccount);
+    await settleAuctionIx(fund, members[1]).rpc();
+    assert.equal((await balance(members[1].tokenAccount)) - balanceBefore, 190_000_000);
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(chitFund.currentCycle, 1);
+  });
+});
+
+describe("Emergency requests", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(100), mint, { cycles: 3, cycleDuration: 3 }));
+  });
+
+  it("Withdraws a request once the quorum is out of reach", async () => {
+    await requestEmergencyIx(fund, members[0]).rpc();
+    await expectError((await voteEmergencyIx(fund, members[0], members[0], true)).rpc(), "CannotVoteOwnRequest");
+
+    // One of the two other members against leaves no majority possible
+    await (await voteEmergencyIx(fund, members[1], members[0], false)).rpc();
+    const requester = await program.account.participant.fetch(participantPDA(fund.fund, members[0].keypair.publicKey));
+    assert.isFalse(requester.isEmergencyRequested);
+    assert.isFalse(requester.emergencyApproved);
+  });
+
+  it("Queues a request approved while another is waiting for its pot", async () => {
+    await requestEmergencyIx(fund, members[0]).rpc();
+    await requestEmergencyIx(fund, members[1]).rpc();
+
+    await (await voteEmergencyIx(fund, members[1], members[0], true)).rpc();
+    await (await voteEmergencyIx(fund, members[2], members[0], true)).rpc();
+    await (await voteEmergencyIx(fund, members[0], members[1], true)).rpc();
+    await (await voteEmergencyIx(fund, members[2], members[1], true)).rpc();
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.isTrue(chitFund.priorityBorrower.equals(members[0].keypair.publicKey));
+    assert.isTrue(chitFund.emergencyQueue[1]);
+    const queued = await program.account.participant.fetch(participantPDA(fund.fund, members[1].keypair.publicKey));
+    assert.isTrue(queued.emergencyApproved);
+
+    // No new request can be made while one is waiting
+    await expectError(requestEmergencyIx(fund, members[2]).rpc(), "PriorityBorrowerPending");
+  });
+
+  it("Pays the first request and moves the queued one up", async () => {
+    await waitUntil((await cycleTimes(fund)).end);
+    await drawWinnerIx(fund).rpc();
+    await claimPayoutIx(fund, members[0]).rpc();
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.isTrue(chitFund.priorityBorrower.equals(members[1].keypair.publicKey));
+    assert.isFalse(chitFund.emergencyQueue[1]);
+  });
+});
+
+describe("Late contribution", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    fund = await initFund(new anchor.BN(90), mint, { deadlineOffset: 2, gracePeriod: 60, lateFeeBps: 1_000 });
+    members = [await newMember(fund), await newMember(fund)];
+    for (const member of members) {
+      await join(fund, member);
+    }
+    await startFund(fund);
+    await contribute(fund, members[0]);
+  });
+
+  it("Charges a late fee after the deadline", async () => {
+    await waitUntil((await cycleTimes(fund)).deadline + 1);
+
+    const balanceBefore = await balance(members[1].tokenAccount);
+    await contribute(fund, members[1]);
+    assert.equal(balanceBefore - (await balance(members[1].tokenAccount)), CONTRIBUTION + 10_000_000);
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(chitFund.lateFeesCollected.toNumber(), 10_000_000);
+  });
+
+  it("Shares the fee with every member but the late payer", async () => {
+    const balanceBefore = await balance(members[0].tokenAccount);
+    await claimDividendIx(fund, members[0]).rpc();
+    assert.equal((await balance(members[0].tokenAccount)) - balanceBefore, 10_000_000);
+
+    await expectError(claimDividendIx(fund, members[1]).rpc(), "NoDividendAvailable");
+  });
+});
+
+describe("Auction dividends", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(40), mint, {
+      cycles: 3,
+      cycleDuration: 3,
+      selectionMode: { auction: {} },
+      maxDiscountBps: 1_000,
+    }));
+    await placeBidIx(fund, members[0], 20_000_000).rpc();
+    await waitUntil((await cycleTimes(fund)).end);
+    await closeAuction(fund);
+    await settleAuctionIx(fund, members[0]).rpc();
+  });
+
+  it("Shares the winning discount among the other members", async () => {
+    for (const member of members.slice(1)) {
+      const balanceBefore = await balance(member.tokenAccount);
+      await claimDividendIx(fund, member).rpc();
+      assert.equal((await balance(member.tokenAccount)) - balanceBefore, 10_000_000);
+    }
+  });
+
+  it("Pays nothing to the winner or to a member claiming twice", async () => {
+    await expectError(claimDividendIx(fund, members[0]).rpc(), "NoDividendAvailable");
+    await expectError(claimDividendIx(fund, members[1]).rpc(), "NoDividendAvailable");
+  });
+});
+
+describe("Cancelled fund", () => {
+  it("Lets the creator cancel during enrollment and refunds the collateral", async () => {
+    const mint = await newAllowedMint();
+    const fund = await initFund(new anchor.BN(120), mint);
+    const member = await newMember(fund);
+    await join(fund, member);
+
+    // Only the creator may cancel while enrollment is still open
+    await expectError(cancelFundIx(fund, member.keypair).rpc(), "CannotCancelFund");
+    await cancelFundIx(fund).rpc();
+
+    const balanceBefore = await balance(member.tokenAccount);
+    await claimRefundIx(fund, member).rpc();
+    assert.equal((await balance(member.tokenAccount)) - balanceBefore, COLLATERAL);
+
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, member.keypair.publicKey));
+    assert.isTrue(participant.isRefunded);
+    await expectError(claimRefundIx(fund, member).rpc(), "RefundAlreadyClaimed");
+  });
+
+  it("Lets anyone cancel once enrollment has failed", async () => {
+    const mint = await newAllowedMint();
+    const fund = await initFund(new anchor.BN(121), mint, { enrollmentWindow: 3 });
+    const member = await newMember(fund);
+    await join(fund, member);
+
+    const { enrollmentDeadline } = await program.account.chitFund.fetch(fund.fund);
+    await waitUntil(enrollmentDeadline.toNumber());
+    await expectError(join(fund, await newMember(fund)), "EnrollmentClosed");
+    await cancelFundIx(fund, member.keypair).rpc();
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.deepEqual(chitFund.status, { cancelled: {} });
+  });
+});
+
+describe("Collateral withdrawal", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    ({ fund, members } = await completedFund(new anchor.BN(150)));
+  });
+
+  it("Returns the remaining collateral once", async () => {
+    const balanceBefore = await balance(members[0].collateralAccount);
+    await withdrawIx(fund, members[0]).rpc();
+    assert.equal((await balance(members[0].collateralAccount)) - balanceBefore, COLLATERAL);
+
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, members[0].keypair.publicKey));
+    assert.equal(participant.collateralReturned.toNumber(), COLLATERAL);
+  });
+
+  it("Rejects a second withdrawal", async () => {
+    await expectError(withdrawIx(fund, members[0]).rpc(), "CollateralAlreadyWithdrawn");
+  });
+
+  it("Leaves the other member's collateral in the vault", async () => {
+    assert.equal(await balance(fund.collateralVault), COLLATERAL);
+  });
+});
+
+describe("Oracle-priced collateral", () => {
+  let fund: Fund;
+  let member: Member;
+
+  before(async function () {
+    if (!(await setTestPrice(2, 0))) {
+      this.skip();
+    }
+
+    const mint = await newAllowedMint(TOKEN_PROGRAM_ID, 6);
+    const collateralMint = await newAllowedMint(TOKEN_PROGRAM_ID, 9);
+    fund = await initFund(new anchor.BN(170), mint, { collateralMint, priceFeed: testOraclePDA });
+    member = await newMember(fund, 1_000 * 10 ** 9);
+  });
+
+  it("Rejects a price that is not positive", async () => {
+    await expectError(setTestPrice(0, 0), "InvalidPrice");
+  });
+
+  it("Reads collateral prices only from the fund's own feed", async () => {
+    await expectError(joinIx({ ...fund, priceFeed: null }, member).rpc(), "PriceFeedRequired");
+    await expectError(joinIx({ ...fund, priceFeed: configPDA }, member).rpc(), "InvalidPriceFeed");
+  });
+
+  it("Values collateral at the feed's current price", async () => {
+    // At four contribution tokens apiece, 200 is covered by 50 collateral tokens
+    await setTestPrice(4, 0);
+    await join(fund, member);
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, member.keypair.publicKey));
+    assert.equal(participant.collateralDeposited.toString(), (50 * 10 ** 9).toString());
+  });
+});
+
+describe("Liquidation", () => {
+  let fund: Fund;
+  let members: Member[];
+  let liquidatorAccount: PublicKey;
+
+  function liquidateIx(member: Member) {
+    return program.methods.liquidate().accountsPartial({
+      liquidator: provider.wallet.publicKey,
+      mint: fund.mint,
+      collateralMint: fund.collateralMint,
+      chitFund: fund.fund,
+      collateralVault: fund.collateralVault,
+      participant: participantPDA(fund.fund, member.keypair.publicKey),
+      liquidatorCollateralAccount: liquidatorAccount,
+      priceFeed: fund.priceFeed,
+      collateralTokenProgram: fund.collateralTokenProgram,
+    });
+  }
+
+  before(async function () {
+    // One collateral token is worth two contribution tokens, so each member posts 100
+    if (!(await setTestPrice(2, 0))) {
+      this.skip();
+    }
+
+    const mint = await newAllowedMint(TOKEN_PROGRAM_ID, 6);
+    const collateralMint = await newAllowedMint(TOKEN_PROGRAM_ID, 9);
+    fund = await initFund(new anchor.BN(180), mint, {
+      collateralMint,
+      priceFeed: testOraclePDA,
+      cycles: 3,
+      cycleDuration: 3,
+      selectionMode: { fixedOrder: {} },
+      payoutOrder: [0, 1, 2],
+    });
+    members = [];
+    for (let i = 0; i < 3; i++) {
+      const member = await newMember(fund, 1_000 * 10 ** 9);
+      await join(fund, member);
+      members.push(member);
+    }
+    await startFund(fund);
+    for (const member of members) {
+      await contribute(fund, member);
+    }
+    liquidatorAccount = await fundedTokenAccount(collateralMint, provider.wallet.publicKey, TOKEN_PROGRAM_ID, 0);
+
+    await waitUntil((await cycleTimes(fund)).end);
+    await setTestPrice(2, 0);
+    await claimPayoutIx(fund, members[0]).rpc();
+  });
+
+  it("Only liquidates members who have borrowed and fallen below the margin", async () => {
+    await expectError(liquidateIx(members[1]).rpc(), "LiquidationBeforeBorrowing");
+    // 100 collateral tokens at two apiece still cover the 200 owed
+    await expectError(liquidateIx(members[0]).rpc(), "CollateralAboveMaintenance");
+  });
+
+  it("Reserves the collateral and pays the liquidator a bounty after a price fall", async () => {
+    // At one apiece the collateral covers half of what is owed
+    await setTestPrice(1, 0);
+    await liquidateIx(members[0]).rpc();
+
+    // Not enough to cover the 200 owed, so the reserve and bounty shrink in proportion
+    const bounty = Math.floor((100 * 10 ** 9 * 500) / 10_500);
+    assert.equal(await balance(liquidatorAccount), bounty);
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, members[0].keypair.publicKey));
+    assert.isTrue(participant.isLiquidated);
+    assert.equal(participant.liquidationReserve.toNumber(), 100 * 10 ** 9 - bounty);
+
+    await expectError(liquidateIx(members[0]).rpc(), "AlreadyLiquidated");
+  });
+});
+
+describe("Payout holdback", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(190), mint, {
+      cycleDuration: 3,
+      selectionMode: { fixedOrder: {} },
+      payoutOrder: [0, 1],
+      holdback: true,
+    }));
+    await waitUntil((await cycleTimes(fund)).end);
+  });
+
+  it("Holds back what the winner still owes", async () => {
+    const balanceBefore = await balance(members[0].tokenAccount);
+    await claimPayoutIx(fund, members[0]).rpc();
+    assert.equal((await balance(members[0].tokenAccount)) - balanceBefore, CONTRIBUTION);
+
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, members[0].keypair.publicKey));
+    assert.equal(participant.payoutEscrow.toNumber(), CONTRIBUTION);
+  });
+
+  it("Releases the holdback as the winner keeps paying", async () => {
+    const balanceBefore = await balance(members[0].tokenAccount);
+    await contribute(fund, members[0]);
+    assert.equal(await balance(members[0].tokenAccount), balanceBefore);
+
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, members[0].keypair.publicKey));
+    assert.equal(participant.payoutEscrow.toNumber(), 0);
+    assert.equal(participant.payoutReceived.toNumber(), 2 * CONTRIBUTION);
+  });
+});
+
+describe("Risky mints", () => {
+  it("Rejects a mint with a freeze authority", async () => {
+    const mint = await createMint(provider.connection, payer, payer.publicKey, payer.publicKey, 6);
+    await program.methods
+      .allowMint()
+      .accountsPartial({ admin: provider.wallet.publicKey, config: configPDA, mint })
+      .rpc();
+
+    const { builder } = initFundIx(new anchor.BN(220), mint);
+    await expectError(builder.rpc(), "MintHasFreezeAuthority");
+  });
+
+  it("Rejects a transfer-fee mint unless the policy allows it", async () => {
+    const mint = await newTransferFeeMint(100);
+    const { builder } = initFundIx(new anchor.BN(221), mint, {}, TOKEN_2022_PROGRAM_ID);
+    await expectError(builder.rpc(), "MintHasTransferFee");
+  });
+});
+
+describe("Transfer-fee mint", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  before(async () => {
+    // 1% of every transfer is withheld by the mint
+    const mint = await newTransferFeeMint(100);
+    await setMintPolicy(true);
+    try {
+      ({ fund, members } = await activeFund(
+        new anchor.BN(230),
+        mint,
+        { cycleDuration: 3, selectionMode: { fixedOrder: {} }, payoutOrder: [0, 1] },
+        TOKEN_2022_PROGRAM_ID
+      ));
+    } finally {
+      await setMintPolicy(false);
+    }
+  });
+
+  it("Has members cover the fee so the vaults receive the full amounts", async () => {
+    assert.equal(await balance(fund.contributionVault, TOKEN_2022_PROGRAM_ID), 2 * CONTRIBUTION);
+    assert.equal(await balance(fund.collateralVault, TOKEN_2022_PROGRAM_ID), 2 * COLLATERAL);
+
+    const chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.equal(chitFund.totalContributionAmount.toNumber(), 2 * CONTRIBUTION);
+  });
+
+  it("Has the winner bear the fee on the way out", async () => {
+    await waitUntil((await cycleTimes(fund)).end);
+    const balanceBefore = await balance(members[0].tokenAccount, TOKEN_2022_PROGRAM_ID);
+    await claimPayoutIx(fund, members[0]).rpc();
+
+    // 1% of the 200 pot stays with the mint
+    assert.equal((await balance(members[0].tokenAccount, TOKEN_2022_PROGRAM_ID)) - balanceBefore, 198_000_000);
+    assert.equal(await balance(fund.contributionVault, TOKEN_2022_PROGRAM_ID), 0);
+  });
+});
+
+describe("Vote fund", () => {
+  let fund: Fund;
+  let members: Member[];
+
+  function votePayoutIx(voter: Member, candidate: PublicKey) {
+    const [vote] = PublicKey.findProgramAddressSync(
+      [Buffer.from("payout_vote"), fund.fund.toBuffer(), Buffer.from([0]), voter.keypair.publicKey.toBuffer()],
+      program.programId
+    );
+    return program.methods
+      .votePayout(candidate)
+      .accountsPartial({
+        user: voter.keypair.publicKey,
+        chitFund: fund.fund,
+        participant: participantPDA(fund.fund, voter.keypair.publicKey),
+        vote,
+      })
+      .signers([voter.keypair]);
+  }
+
+  before(async () => {
+    const mint = await newAllowedMint();
+    ({ fund, members } = await activeFund(new anchor.BN(250), mint, {
+      cycles: 3,
+      cycleDuration: 3,
+      selectionMode: { vote: {} },
+    }));
+  });
+
+  it("Only takes votes for members of the fund", async () => {
+    await expectError(votePayoutIx(members[0], Keypair.generate().publicKey).rpc(), "InvalidVoteCandidate");
+  });
+
+  it("Selects the first member backed by a majority", async () => {
+    await votePayoutIx(members[0], members[2].keypair.publicKey).rpc();
+    let chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.isTrue(chitFund.selectedBorrower.equals(PublicKey.default));
+
+    await votePayoutIx(members[1], members[2].keypair.publicKey).rpc();
+    chitFund = await program.account.chitFund.fetch(fund.fund);
+    assert.isTrue(chitFund.selectedBorrower.equals(members[2].keypair.publicKey));
+    await expectError(votePayoutIx(members[2], members[0].keypair.publicKey).rpc(), "WinnerAlreadyDrawn");
+  });
+
+  it("Pays only the selected member", async () => {
+    await waitUntil((await cycleTimes(fund)).end);
+    await expectError(claimPayoutIx(fund, members[0]).rpc(), "ConstraintSeeds");
+
+    const balanceBefore = await balance(members[2].tokenAccount);
+    await claimPayoutIx(fund, members[2]).rpc();
+    assert.equal((await balance(members[2].tokenAccount)) - balanceBefore, 3 * CONTRIBUTION);
+  });
+});
+
+describe("Fixed order fund", () => {
+  it("Rejects an order that is not a permutation of the members", async () => {
+    const mint = await newAllowedMint();
+    const { builder } = initFundIx(new anchor.BN(251), mint, {
+      selectionMode: { fixedOrder: {} },
+      payoutOrder: [0, 0],
+    });
+    await expectError(builder.rpc(), "InvalidPayoutOrder");
+  });
+
+  it("Pays members in the agreed order", async () => {
+    const mint = await newAllowedMint();
+    const { fund, members } = await activeFund(new anchor.BN(252), mint, {
+      cycleDuration: 3,
+      selectionMode: { fixedOrder: {} },
+      payoutOrder: [1, 0],
+    });
+    await waitUntil((await cycleTimes(fund)).end);
+    await expectError(claimPayoutIx(fund, members[0]).rpc(), "ConstraintSeeds");
+    await claimPayoutIx(fund, members[1]).rpc();
+
+    const participant = await program.account.participant.fetch(participantPDA(fund.fund, members[1].keypair.publicKey));
+    assert.equal(participant.borrowedCycle, 0);
   });
-});
\ No newline at end of file
+});